@@ -92,6 +92,14 @@ extern "C" {
     pub fn PyThreadState_Next(arg1: *mut PyThreadState) -> *mut PyThreadState;
 }
 
+/// Emulation of Python 3.4's `PyGILState_Check()`:
+/// returns 1 if the current thread holds the GIL.
+#[inline]
+pub unsafe fn PyGILState_Check() -> c_int {
+    let tstate = _PyThreadState_Current;
+    (!tstate.is_null() && tstate == PyGILState_GetThisThreadState()) as c_int
+}
+
 #[cfg(py_sys_config="Py_DEBUG")]
 #[inline(always)]
 pub unsafe fn PyThreadState_GET() -> *mut PyThreadState {
@@ -1,3 +1,4 @@
+use std::ptr;
 use libc::{c_char, c_int, FILE};
 use object::*;
 use code::*;
@@ -119,3 +120,7 @@ extern "C" {
     fn _Py_hgversion() -> *const c_char;
 }
 
+#[inline]
+pub unsafe fn Py_CompileString(string: *const c_char, p: *const c_char, s: c_int) -> *mut PyObject {
+    Py_CompileStringFlags(string, p, s, ptr::null_mut())
+}
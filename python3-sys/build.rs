@@ -290,7 +290,7 @@ print(sys.exec_prefix);";
 /// cargo vars to stdout.
 ///
 /// Note that if the python doesn't satisfy expected_version, this will error.
-fn configure_from_path(expected_version: &PythonVersion) -> Result<String, String> {
+fn configure_from_path(expected_version: &PythonVersion) -> Result<(PythonVersion, String), String> {
     let (interpreter_version, interpreter_path, lines) = 
         try!(find_interpreter_and_get_config(expected_version));
     let libpath: &str = &lines[1];
@@ -318,7 +318,7 @@ fn configure_from_path(expected_version: &PythonVersion) -> Result<String, Strin
         println!("cargo:rustc-link-search=native={}\\libs", exec_prefix);
     }
 
-    return Ok(interpreter_path);
+    return Ok((interpreter_version, interpreter_path));
 }
 
 /// Determine the python version we're supposed to be building
@@ -361,8 +361,20 @@ fn main() {
     // try using 'env' (sorry but this isn't our fault - it just has to 
     // match the pkg-config package name, which is going to have a . in it).
     let version = version_from_env().unwrap();
-    let python_interpreter_path = configure_from_path(&version).unwrap();
-    let config_map = get_config_vars(&python_interpreter_path).unwrap();
+    let (interpreter_version, python_interpreter_path) = configure_from_path(&version).unwrap();
+    let mut config_map = get_config_vars(&python_interpreter_path).unwrap();
+
+    // The interpreter version and the limited API setting are exported
+    // as flags as well, so that dependents can use e.g.
+    // #[cfg(py_sys_config="Py_3_4")] for version-specific code.
+    if let PythonVersion { major: 3, minor: Some(minor) } = interpreter_version {
+        for i in 4..(minor+1) {
+            config_map.insert(format!("Py_3_{}", i), "1".to_owned());
+        }
+    }
+    if env::var_os("CARGO_FEATURE_PEP_384").is_some() {
+        config_map.insert("Py_LIMITED_API".to_owned(), "1".to_owned());
+    }
     for (key, val) in &config_map {
         match cfg_line_for_var(key, val) {
             Some(line) => println!("{}", line),
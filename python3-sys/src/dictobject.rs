@@ -1,5 +1,5 @@
 use libc::{c_char, c_int};
-use pyport::Py_ssize_t;
+use pyport::{Py_ssize_t, Py_hash_t};
 use object::*;
 
 extern "C" {
@@ -86,3 +86,16 @@ extern "C" {
      -> c_int;
 }
 
+#[cfg(not(Py_LIMITED_API))]
+extern "C" {
+    #[cfg(Py_3_4)]
+    pub fn PyDict_SetDefault(mp: *mut PyObject, key: *mut PyObject,
+                             defaultobj: *mut PyObject) -> *mut PyObject;
+    #[cfg(Py_3_5)]
+    pub fn _PyDict_GetItem_KnownHash(mp: *mut PyObject, key: *mut PyObject,
+                                     hash: Py_hash_t) -> *mut PyObject;
+    #[cfg(Py_3_5)]
+    pub fn _PyDict_SetItem_KnownHash(mp: *mut PyObject, key: *mut PyObject,
+                                     item: *mut PyObject, hash: Py_hash_t) -> c_int;
+}
+
@@ -2,6 +2,14 @@ use libc::c_int;
 use pyport::Py_ssize_t;
 use object::*;
 
+#[repr(C)]
+#[cfg(not(Py_LIMITED_API))]
+pub struct PyListObject {
+    pub ob_base: PyVarObject,
+    pub ob_item: *mut *mut PyObject,
+    pub allocated: Py_ssize_t,
+}
+
 extern "C" {
     pub static mut PyList_Type: PyTypeObject;
     pub static mut PyListIter_Type: PyTypeObject;
@@ -18,6 +26,19 @@ pub unsafe fn PyList_CheckExact(op : *mut PyObject) -> c_int {
     (Py_TYPE(op) == &mut PyList_Type) as c_int
 }
 
+// Macro, trading safety for speed
+#[inline(always)]
+#[cfg(not(Py_LIMITED_API))]
+pub unsafe fn PyList_GET_ITEM(op: *mut PyObject, i: Py_ssize_t) -> *mut PyObject {
+   *(*(op as *mut PyListObject)).ob_item.offset(i as isize)
+}
+
+#[inline(always)]
+#[cfg(not(Py_LIMITED_API))]
+pub unsafe fn PyList_GET_SIZE(op: *mut PyObject) -> Py_ssize_t {
+    Py_SIZE(op)
+}
+
 extern "C" {
     pub fn PyList_New(size: Py_ssize_t) -> *mut PyObject;
     pub fn PyList_Size(arg1: *mut PyObject) -> Py_ssize_t;
@@ -58,3 +58,7 @@ extern "C" {
      -> c_long;
 }
 
+#[cfg(not(Py_LIMITED_API))]
+extern "C" {
+    pub fn _PyLong_Sign(v: *mut PyObject) -> c_int;
+}
@@ -39,6 +39,23 @@ extern "C" {
     pub fn PyGILState_Ensure() -> PyGILState_STATE;
     pub fn PyGILState_Release(arg1: PyGILState_STATE) -> ();
     pub fn PyGILState_GetThisThreadState() -> *mut PyThreadState;
+    #[cfg(Py_3_4)]
+    pub fn PyGILState_Check() -> c_int;
+}
+
+#[cfg(not(Py_3_4))]
+extern "C" {
+    // Declared as `_Py_atomic_address`, a struct containing a single pointer.
+    static mut _PyThreadState_Current: *mut PyThreadState;
+}
+
+/// Emulation of Python 3.4's `PyGILState_Check()`:
+/// returns 1 if the current thread holds the GIL.
+#[cfg(not(Py_3_4))]
+#[inline]
+pub unsafe fn PyGILState_Check() -> c_int {
+    let tstate = _PyThreadState_Current;
+    (!tstate.is_null() && tstate == PyGILState_GetThisThreadState()) as c_int
 }
 
 #[inline(always)]
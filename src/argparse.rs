@@ -22,7 +22,8 @@
 use std::ptr;
 use python::{Python, PythonObject};
 use objects::{PyObject, PyTuple, PyDict, PyString, exc};
-use conversion::ToPyObject;
+use conversion::{ToPyObject, ExtractPyObject};
+use objectprotocol::ObjectProtocol;
 use ffi;
 use err::{self, PyResult};
 
@@ -34,6 +35,28 @@ pub struct ParamDescription<'a> {
     pub is_optional: bool
 }
 
+/// A boolean flag parameter that accepts any Python object and uses its truthiness,
+/// like the Python expression `bool(obj)`.
+///
+/// Use `PyFlag` as parameter type in `py_argparse!` (or `py_fn!`) for arguments
+/// like `verbose=1` or `strict=[]`, where `bool` would only accept `True` and `False`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PyFlag(pub bool);
+
+impl <'prepared> ExtractPyObject<'prepared> for PyFlag {
+    type Prepared = bool;
+
+    #[inline]
+    fn prepare_extract(py: Python, obj: &PyObject) -> PyResult<bool> {
+        obj.is_true(py)
+    }
+
+    #[inline]
+    fn extract(_py: Python, prepared: &'prepared bool) -> PyResult<PyFlag> {
+        Ok(PyFlag(*prepared))
+    }
+}
+
 /// Parse argument list
 ///
 /// * fname:  Name of the current function
@@ -122,6 +145,19 @@ macro_rules! py_argparse_extract {
         py_argparse_extract!($py, $iter, ($pname: $ptype) {
             py_argparse_extract!( $py, $iter, ( $($r)* ) $body)
         })
+    };
+    ( $py:ident, $iter:expr, ( $pname:ident : $ptype:ty | parse_with = $conv:expr ) $body:block) => {{
+        let obj = $crate::PyClone::clone_ref($iter.next().unwrap().as_ref().unwrap(), $py);
+        let result: $crate::PyResult<$ptype> = ($conv)(obj);
+        match result {
+            Ok($pname) => $body,
+            Err(e) => Err(e)
+        }
+    }};
+    ( $py: ident, $iter:expr, ( $pname:ident : $ptype:ty | parse_with = $conv:expr , $($r:tt)+ ) $body:block) => {
+        py_argparse_extract!($py, $iter, ($pname: $ptype | parse_with = $conv) {
+            py_argparse_extract!( $py, $iter, ( $($r)* ) $body)
+        })
     }
 }
 
@@ -141,6 +177,9 @@ macro_rules! py_argparse_snd {
 /// * `kwargs`: expression of type `Option<&PyDict>`: The named arguments
 /// * `parameter-list`: a comma-separated list of Rust parameter declarations (`name: type`).
 ///   The types used must implement the `ExtractPyObject` trait.
+///   Alternatively, a parameter can be declared as `name: type | parse_with = conv`,
+///   where `conv` is an expression of type `FnOnce(PyObject) -> PyResult<type>`
+///   that is used instead of `ExtractPyObject` to convert the argument.
 /// * `body`: expression of type `PyResult<_>`.
 ///
 /// `py_argparse!()` expands to code that extracts values from `args` and `kwargs` and assigns
@@ -150,7 +189,8 @@ macro_rules! py_argparse_snd {
 /// If extraction fails, `py_argparse!()` returns a failed `PyResult` without evaluating `body`.
 #[macro_export]
 macro_rules! py_argparse {
-    ($py:expr, $fname:expr, $args:expr, $kwargs:expr, ($( $pname:ident : $ptype:ty ),*) $body:block) => {{
+    ($py:expr, $fname:expr, $args:expr, $kwargs:expr,
+     ($( $pname:ident : $ptype:ty $(| parse_with = $conv:expr)* ),*) $body:block) => {{
         const PARAMS: &'static [$crate::argparse::ParamDescription<'static>] = &[
             $(
                 $crate::argparse::ParamDescription {
@@ -166,7 +206,8 @@ macro_rules! py_argparse {
                 // We can't use experimental slice pattern syntax in macros
                 //let &[$(ref $pname),*] = &output;
                 let mut iter = output.iter();
-                let ret = py_argparse_extract!( py, iter, ( $( $pname : $ptype ),* ) $body );
+                let ret = py_argparse_extract!( py, iter,
+                    ( $( $pname : $ptype $(| parse_with = $conv)* ),* ) $body );
                 assert!(iter.next() == None);
                 ret
             },
@@ -178,7 +219,9 @@ macro_rules! py_argparse {
 #[cfg(test)]
 mod test {
     use python::{Python, PythonObject};
+    use objects::{PyObject, PyList, exc};
     use conversion::ToPyObject;
+    use err::{PyErr, PyResult};
 
     #[test]
     pub fn test_parse() {
@@ -194,5 +237,52 @@ mod test {
         }).unwrap();
         assert!(called);
     }
-}
 
+    #[test]
+    pub fn test_parse_flag() {
+        use super::PyFlag;
+        let gil_guard = Python::acquire_gil();
+        let py = gil_guard.python();
+        let tuple = (1, PyList::new(py, &[]), true).to_py_object(py);
+        py_argparse!(py, None, &tuple, None, (a: PyFlag, b: PyFlag, c: PyFlag) {
+            assert_eq!(PyFlag(true), a);
+            assert_eq!(PyFlag(false), b);
+            assert_eq!(PyFlag(true), c);
+            Ok(())
+        }).unwrap();
+    }
+
+    fn int_or_numeric_str(py: Python, obj: PyObject) -> PyResult<i32> {
+        match obj.extract::<i32>(py) {
+            Ok(v) => Ok(v),
+            Err(_) => {
+                let s = try!(obj.extract::<String>(py));
+                s.trim().parse().map_err(|_| PyErr::new::<exc::ValueError, _>(py,
+                    format!("invalid number: '{}'", s)))
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_parse_with() {
+        let gil_guard = Python::acquire_gil();
+        let py = gil_guard.python();
+        for tuple in &[("abc", 42).to_py_object(py), ("abc", "42").to_py_object(py)] {
+            let mut called = false;
+            py_argparse!(py, None, tuple, None,
+                         (x: &str, y: i32 | parse_with = |obj| int_or_numeric_str(py, obj)) {
+                assert_eq!(x, "abc");
+                assert_eq!(y, 42);
+                called = true;
+                Ok(())
+            }).unwrap();
+            assert!(called);
+        }
+        let tuple = ("abc", "x").to_py_object(py);
+        let result: PyResult<()> = py_argparse!(py, None, &tuple, None,
+                (_x: &str, _y: i32 | parse_with = |obj| int_or_numeric_str(py, obj)) {
+            Ok(())
+        });
+        assert!(result.is_err());
+    }
+}
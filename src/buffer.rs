@@ -0,0 +1,148 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! This module contains support for the Python buffer protocol.
+
+use std::{mem, slice};
+use ffi;
+use python::Python;
+use objects::PyObject;
+use err::{self, PyResult};
+
+/// Marker trait for element types that can be safely viewed in a Python buffer.
+///
+/// Implementing this trait asserts that every bit pattern of the correct size
+/// is a valid value of the type.
+pub unsafe trait BufferElement : Copy {}
+
+unsafe impl BufferElement for u8 {}
+unsafe impl BufferElement for i8 {}
+unsafe impl BufferElement for u16 {}
+unsafe impl BufferElement for i16 {}
+unsafe impl BufferElement for u32 {}
+unsafe impl BufferElement for i32 {}
+unsafe impl BufferElement for u64 {}
+unsafe impl BufferElement for i64 {}
+unsafe impl BufferElement for usize {}
+unsafe impl BufferElement for isize {}
+unsafe impl BufferElement for f32 {}
+unsafe impl BufferElement for f64 {}
+
+/// A writable view into the memory of a Python object that supports the buffer protocol,
+/// for example a `bytearray` or a numpy array.
+///
+/// The buffer is released when the `PyBufferMut` is dropped.
+pub struct PyBufferMut(Box<ffi::Py_buffer>);
+
+impl PyBufferMut {
+    /// Requests a writable, C-contiguous buffer from the object.
+    ///
+    /// Fails with `BufferError` (or `TypeError`) if the object does not support the
+    /// buffer protocol or only provides read-only buffers.
+    pub fn get(py: Python, obj: &PyObject) -> PyResult<PyBufferMut> {
+        unsafe {
+            let mut buf = Box::new(mem::zeroed::<ffi::Py_buffer>());
+            try!(err::error_on_minusone(py,
+                ffi::PyObject_GetBuffer(obj.as_ptr(), &mut *buf, ffi::PyBUF_CONTIG)));
+            Ok(PyBufferMut(buf))
+        }
+    }
+
+    /// Gets the length of the buffer in bytes.
+    #[inline]
+    pub fn len_bytes(&self) -> usize {
+        self.0.len as usize
+    }
+
+    /// Gets the size of a single element, in bytes.
+    #[inline]
+    pub fn item_size(&self) -> usize {
+        self.0.itemsize as usize
+    }
+
+    /// Gets the buffer contents as mutable slice of `T`.
+    ///
+    /// Returns `None` if the element size of the buffer does not match `size_of::<T>()`,
+    /// or if the buffer memory is not suitably aligned for `T`.
+    pub fn as_mut_slice<'a, T>(&'a mut self, _py: Python<'a>) -> Option<&'a mut [T]>
+        where T: BufferElement
+    {
+        let size = mem::size_of::<T>();
+        let ptr = self.0.buf as *mut T;
+        if self.item_size() != size || (ptr as usize) % mem::align_of::<T>() != 0 {
+            return None;
+        }
+        unsafe {
+            Some(slice::from_raw_parts_mut(ptr, self.len_bytes() / size))
+        }
+    }
+
+    /// Releases the buffer.
+    ///
+    /// This is slightly faster than relying on automatic drop, because `release`
+    /// does not need to acquire the GIL.
+    pub fn release(self, _py: Python) {
+        unsafe {
+            let mut buf = ::std::ptr::read(&self.0);
+            mem::forget(self);
+            ffi::PyBuffer_Release(&mut *buf);
+        }
+    }
+}
+
+/// Dropping a `PyBufferMut` releases the buffer,
+/// so that the object may be resized again.
+impl Drop for PyBufferMut {
+    fn drop(&mut self) {
+        let _gil_guard = Python::acquire_gil();
+        unsafe { ffi::PyBuffer_Release(&mut *self.0) }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use python::Python;
+    use objectprotocol::ObjectProtocol;
+    use objects::PyDict;
+
+    #[test]
+    fn test_bytearray_zero() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let d = PyDict::new(py);
+        let ba = py.eval("bytearray(b'abc')", None, None).unwrap();
+        d.set_item(py, "ba", &ba).unwrap();
+        {
+            let mut buf = ba.get_buffer_mut(py).unwrap();
+            assert_eq!(3, buf.len_bytes());
+            for b in buf.as_mut_slice::<u8>(py).unwrap() {
+                *b = 0;
+            }
+        }
+        let ok: bool = py.eval("ba == bytearray(3)", None, Some(&d)).unwrap().extract(py).unwrap();
+        assert!(ok);
+    }
+
+    #[test]
+    fn test_readonly_fails() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let b = py.eval("b'abc'", None, None).unwrap();
+        assert!(b.get_buffer_mut(py).is_err());
+    }
+}
@@ -19,8 +19,8 @@
 use std;
 use ffi;
 use python::{Python, PythonObject, PythonObjectWithCheckedDowncast, PyClone};
-use objects::PyObject;
-use err::PyResult;
+use objects::{PyObject, exc};
+use err::{PyErr, PyResult};
 
 /// Conversion trait that allows various objects to be converted into Python objects.
 pub trait ToPyObject {
@@ -115,6 +115,18 @@ where T: PythonObjectWithCheckedDowncast
     }
 }
 
+/// Replaces a `TypeError` raised while extracting `obj` with a `TypeError`
+/// of the form "expected int, got list", naming the actual type of `obj`.
+/// Other errors are returned unchanged.
+pub fn type_error_expected(py: Python, err: PyErr, obj: &PyObject, expected: &str) -> PyErr {
+    if err.matches(py, py.get_type::<exc::TypeError>().as_object()) {
+        let msg = format!("expected {}, got {}", expected, obj.get_type().name(py));
+        PyErr::new::<exc::TypeError, _>(py, msg)
+    } else {
+        err
+    }
+}
+
 // ToPyObject for references
 impl <'a, T: ?Sized> ToPyObject for &'a T where T: ToPyObject {
     type ObjectType = T::ObjectType;
@@ -137,4 +149,173 @@ impl <'a, T: ?Sized> ToPyObject for &'a T where T: ToPyObject {
     }
 }
 
+/// Implements `ToPyObject` and `ExtractPyObject` for a newtype wrapper
+/// by delegating to the wrapped type.
+///
+/// Syntax: `py_transparent!(Newtype, InnerType)`
+///
+/// `Newtype` must be a tuple struct with a single field of type `InnerType`,
+/// and `InnerType` must implement both traits.
+/// The Python representation of `Newtype` is the same as that of `InnerType`.
+///
+/// # Example
+/// ```
+/// #[macro_use] extern crate cpython;
+/// use cpython::{Python, ToPyObject, PythonObject};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct UserId(u64);
+/// py_transparent!(UserId, u64);
+///
+/// fn main() {
+///     let gil = Python::acquire_gil();
+///     let py = gil.python();
+///     let obj = UserId(42).to_py_object(py).into_object();
+///     assert_eq!(42, obj.extract::<u64>(py).unwrap());
+///     assert_eq!(UserId(42), obj.extract::<UserId>(py).unwrap());
+/// }
+/// ```
+#[macro_export]
+macro_rules! py_transparent {
+    ($name: ident, $inner: ty) => {
+        impl $crate::ToPyObject for $name {
+            type ObjectType = <$inner as $crate::ToPyObject>::ObjectType;
+
+            #[inline]
+            fn to_py_object(&self, py: $crate::Python) -> Self::ObjectType {
+                $crate::ToPyObject::to_py_object(&self.0, py)
+            }
 
+            #[inline]
+            fn into_py_object(self, py: $crate::Python) -> Self::ObjectType {
+                $crate::ToPyObject::into_py_object(self.0, py)
+            }
+        }
+
+        impl <'prepared> $crate::ExtractPyObject<'prepared> for $name {
+            type Prepared = <$inner as $crate::ExtractPyObject<'prepared>>::Prepared;
+
+            #[inline]
+            fn prepare_extract(py: $crate::Python, obj: &$crate::PyObject) -> $crate::PyResult<Self::Prepared> {
+                <$inner as $crate::ExtractPyObject<'prepared>>::prepare_extract(py, obj)
+            }
+
+            #[inline]
+            fn extract(py: $crate::Python, prepared: &'prepared Self::Prepared) -> $crate::PyResult<$name> {
+                <$inner as $crate::ExtractPyObject<'prepared>>::extract(py, prepared).map($name)
+            }
+        }
+    }
+}
+
+/// Implements `ExtractPyObject` for a Rust enum by matching the value
+/// of a Python `enum.Enum` member against the given variant values.
+///
+/// Syntax: `py_enum_extract!(RustEnum: ValueType { Variant1 = value1, ... })`
+///
+/// The `.value` of the member (see `PyObject::enum_value()`) is extracted as `ValueType`
+/// and compared with each `value` using `==`.
+/// Objects that are not `enum.Enum` members raise `TypeError`;
+/// members with an unknown value raise `ValueError` listing the known values.
+///
+/// # Example
+/// ```
+/// #[macro_use] extern crate cpython;
+/// use cpython::{Python, PyDict};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Color { Red, Green }
+/// py_enum_extract!(Color: i32 { Red = 1, Green = 2 });
+///
+/// fn main() {
+///     let gil = Python::acquire_gil();
+///     let py = gil.python();
+///     # if py.import("enum").is_err() { return; }
+///     let d = PyDict::new(py);
+///     py.run("import enum\nclass Color(enum.IntEnum):\n    RED = 1\n    GREEN = 2\n", None, Some(&d)).unwrap();
+///     let green = py.eval("Color.GREEN", None, Some(&d)).unwrap();
+///     assert_eq!(Color::Green, green.extract::<Color>(py).unwrap());
+/// }
+/// ```
+#[macro_export]
+macro_rules! py_enum_extract {
+    ($name: ident : $value_type: ty { $($variant: ident = $value: expr),+ }) => {
+        impl <'prepared> $crate::ExtractPyObject<'prepared> for $name {
+            type Prepared = $crate::PyObject;
+
+            #[inline]
+            fn prepare_extract(py: $crate::Python, obj: &$crate::PyObject) -> $crate::PyResult<$crate::PyObject> {
+                Ok($crate::PyClone::clone_ref(obj, py))
+            }
+
+            fn extract(py: $crate::Python, obj: &'prepared $crate::PyObject) -> $crate::PyResult<$name> {
+                let value: $value_type = try!(try!(obj.enum_value(py)).extract(py));
+                $(
+                    if value == $value {
+                        return Ok($name::$variant);
+                    }
+                )+
+                let known: Vec<String> = vec![$(format!("{:?}", $value)),+];
+                Err($crate::PyErr::new::<$crate::exc::ValueError, _>(py,
+                    format!("unknown {} value {:?}, expected one of: {}",
+                        stringify!($name), value, known.join(", "))))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use python::{Python, PythonObject};
+    use conversion::ToPyObject;
+
+    #[derive(Debug, PartialEq)]
+    struct UserId(u64);
+    py_transparent!(UserId, u64);
+
+    #[derive(Debug, PartialEq)]
+    struct Name(String);
+    py_transparent!(Name, String);
+
+    #[test]
+    fn test_transparent() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = UserId(42).to_py_object(py).into_object();
+        assert_eq!(42, obj.extract::<u64>(py).unwrap());
+        assert_eq!(UserId(42), obj.extract::<UserId>(py).unwrap());
+
+        let obj = Name("abc".to_owned()).to_py_object(py).into_object();
+        assert_eq!("abc", obj.extract::<String>(py).unwrap());
+        assert_eq!(Name("abc".to_owned()), obj.extract::<Name>(py).unwrap());
+        assert!(obj.extract::<UserId>(py).is_err());
+    }
+
+    #[test]
+    #[cfg(feature="python3-sys")]
+    fn test_enum_extract() {
+        use objects::{PyDict, exc};
+
+        #[derive(Debug, PartialEq)]
+        enum Color { Red, Green, Blue }
+        py_enum_extract!(Color: i32 { Red = 1, Green = 2, Blue = 3 });
+
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let d = PyDict::new(py);
+        py.run("import enum\n\
+                class Color(enum.IntEnum):\n\
+                \x20   RED = 1\n\
+                \x20   GREEN = 2\n\
+                \x20   BLUE = 3\n\
+                \x20   PURPLE = 4\n", None, Some(&d)).unwrap();
+        let get = |name: &str| py.eval(&format!("Color.{}", name), None, Some(&d)).unwrap();
+        assert_eq!(Color::Red, get("RED").extract::<Color>(py).unwrap());
+        assert_eq!(Color::Green, get("GREEN").extract::<Color>(py).unwrap());
+        assert_eq!(Color::Blue, get("BLUE").extract::<Color>(py).unwrap());
+        let err = get("PURPLE").extract::<Color>(py).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::ValueError>().as_object()));
+        // plain ints are not enum members
+        assert!(2.to_py_object(py).into_object().extract::<Color>(py).is_err());
+    }
+}
@@ -26,6 +26,42 @@ use libc;
 use conversion::ToPyObject;
 use std::ffi::CString;
 
+/// Unwraps a `PyResult` inside an `extern "C"` function called by the Python interpreter.
+///
+/// Macro syntax: `py_try!(py, expr)`
+///
+/// If `expr` evaluates to `Ok(v)`, the macro evaluates to `v`.
+/// If `expr` evaluates to `Err(e)`, the exception `e` is restored into the
+/// interpreter state and the enclosing function returns a null pointer,
+/// signalling the error to the caller.
+///
+/// # Example
+/// ```
+/// #[macro_use] extern crate cpython;
+/// use cpython::{Python, PyObject, ObjectProtocol};
+/// use cpython::_detail::ffi;
+///
+/// unsafe extern "C" fn get_len(obj: *mut ffi::PyObject) -> *mut ffi::PyObject {
+///     let py = Python::assume_gil_acquired();
+///     let obj = PyObject::from_borrowed_ptr(py, obj);
+///     let len = py_try!(py, obj.len(py));
+///     ffi::PyLong_FromSsize_t(len as ffi::Py_ssize_t)
+/// }
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! py_try {
+    ($py: expr, $e: expr) => {
+        match $e {
+            Ok(v) => v,
+            Err(e) => {
+                $crate::PyErr::restore(e, $py);
+                return ::std::ptr::null_mut();
+            }
+        }
+    }
+}
+
 /// Represents a Python exception that was raised.
 #[derive(Debug)]
 pub struct PyErr {
@@ -39,7 +75,11 @@ pub struct PyErr {
     /// Call `PyErr::instance()` to get the exception instance in all cases.
     pub pvalue : Option<PyObject>,
     /// The `PyTraceBack` object associated with the error.
-    pub ptraceback : Option<PyObject>
+    pub ptraceback : Option<PyObject>,
+    /// The Rust call stack at the point where the `PyErr` was created.
+    /// Only available with the `backtrace` feature; never exposed to Python.
+    #[cfg(feature="backtrace")]
+    pub rust_backtrace : ::backtrace::Backtrace
 }
 
 
@@ -47,6 +87,39 @@ pub struct PyErr {
 pub type PyResult<T> = Result<T, PyErr>;
 
 impl PyErr {
+    /// Creates a new PyErr from the exception type, value and traceback.
+    /// This is the opposite of `into_normalized_parts()`.
+    ///
+    /// `ptype` should be an exception type; see the field documentation for
+    /// the accepted forms of `pvalue`.
+    #[cfg(not(feature="backtrace"))]
+    #[inline]
+    pub fn from_parts(ptype: PyObject, pvalue: Option<PyObject>, ptraceback: Option<PyObject>) -> PyErr {
+        PyErr { ptype: ptype, pvalue: pvalue, ptraceback: ptraceback }
+    }
+
+    /// Creates a new PyErr from the exception type, value and traceback.
+    /// This is the opposite of `into_normalized_parts()`.
+    ///
+    /// `ptype` should be an exception type; see the field documentation for
+    /// the accepted forms of `pvalue`.
+    #[cfg(feature="backtrace")]
+    pub fn from_parts(ptype: PyObject, pvalue: Option<PyObject>, ptraceback: Option<PyObject>) -> PyErr {
+        PyErr {
+            ptype: ptype,
+            pvalue: pvalue,
+            ptraceback: ptraceback,
+            rust_backtrace: ::backtrace::Backtrace::new()
+        }
+    }
+
+    /// Gets the Rust backtrace captured when this error was created.
+    #[cfg(feature="backtrace")]
+    #[inline]
+    pub fn rust_backtrace(&self) -> &::backtrace::Backtrace {
+        &self.rust_backtrace
+    }
+
     /// Gets whether an error is present in the Python interpreter's global state.
     #[inline]
     pub fn occurred(_ : Python) -> bool {
@@ -69,15 +142,15 @@ impl PyErr {
     unsafe fn new_from_ffi_tuple(py: Python, ptype: *mut ffi::PyObject, pvalue: *mut ffi::PyObject, ptraceback: *mut ffi::PyObject) -> PyErr {
         // Note: must not panic to ensure all owned pointers get acquired correctly,
         // and because we mustn't panic in normalize().
-        PyErr {
-            ptype: if ptype.is_null() {
-                        py.get_type::<exc::SystemError>().into_object()
-                   } else {
-                        PyObject::from_owned_ptr(py, ptype)
-                   },
-            pvalue: PyObject::from_owned_ptr_opt(py, pvalue),
-            ptraceback: PyObject::from_owned_ptr_opt(py, ptraceback)
-        }
+        PyErr::from_parts(
+            if ptype.is_null() {
+                py.get_type::<exc::SystemError>().into_object()
+            } else {
+                PyObject::from_owned_ptr(py, ptype)
+            },
+            PyObject::from_owned_ptr_opt(py, pvalue),
+            PyObject::from_owned_ptr_opt(py, ptraceback)
+        )
     }
 
     /// Creates a new PyErr of type `T`.
@@ -96,11 +169,7 @@ impl PyErr {
 
     fn new_helper(_py: Python, ty: PyType, value: PyObject) -> PyErr {
         assert!(unsafe { ffi::PyExceptionClass_Check(ty.as_object().as_ptr()) } != 0);
-        PyErr {
-            ptype: ty.into_object(),
-            pvalue: Some(value),
-            ptraceback: None
-        }
+        PyErr::from_parts(ty.into_object(), Some(value), None)
     }
 
     /// Creates a new PyErr.
@@ -114,23 +183,19 @@ impl PyErr {
 
     fn from_instance_helper(py: Python, obj: PyObject) -> PyErr {
         if unsafe { ffi::PyExceptionInstance_Check(obj.as_ptr()) } != 0 {
-            PyErr {
-                ptype: unsafe { PyObject::from_borrowed_ptr(py, ffi::PyExceptionInstance_Class(obj.as_ptr())) },
-                pvalue: Some(obj),
-                ptraceback: None
-            }
+            PyErr::from_parts(
+                unsafe { PyObject::from_borrowed_ptr(py, ffi::PyExceptionInstance_Class(obj.as_ptr())) },
+                Some(obj),
+                None
+            )
         } else if unsafe { ffi::PyExceptionClass_Check(obj.as_ptr()) } != 0 {
-            PyErr {
-                ptype: obj,
-                pvalue: None,
-                ptraceback: None
-            }
+            PyErr::from_parts(obj, None, None)
         } else {
-            PyErr {
-                ptype: py.get_type::<exc::TypeError>().into_object(),
-                pvalue: Some("exceptions must derive from BaseException".to_py_object(py).into_object()),
-                ptraceback: None
-            }
+            PyErr::from_parts(
+                py.get_type::<exc::TypeError>().into_object(),
+                Some("exceptions must derive from BaseException".to_py_object(py).into_object()),
+                None
+            )
         }
     }
 
@@ -139,11 +204,7 @@ impl PyErr {
     /// `value` is the exception instance, or a tuple of arguments to pass to the exception constructor.
     #[inline]
     pub fn new_lazy_init(exc: PyType, value: Option<PyObject>) -> PyErr {
-        PyErr {
-            ptype: exc.into_object(),
-            pvalue: value,
-            ptraceback: None
-        }
+        PyErr::from_parts(exc.into_object(), value, None)
     }
 
     /// Print a standard traceback to sys.stderr.
@@ -179,14 +240,33 @@ impl PyErr {
     /// Helper function for normalizing the error by deconstructing and reconstructing the PyErr.
     /// Must not panic for safety in normalize()
     fn into_normalized(self, py: Python) -> PyErr {
+        #[cfg(not(feature="backtrace"))]
         let PyErr { ptype, pvalue, ptraceback } = self;
+        #[cfg(feature="backtrace")]
+        let PyErr { ptype, pvalue, ptraceback, rust_backtrace } = self;
         let mut ptype = ptype.steal_ptr();
         let mut pvalue = pvalue.steal_ptr(py);
         let mut ptraceback = ptraceback.steal_ptr(py);
-        unsafe {
+        let err = unsafe {
             ffi::PyErr_NormalizeException(&mut ptype, &mut pvalue, &mut ptraceback);
             PyErr::new_from_ffi_tuple(py, ptype, pvalue, ptraceback)
-        }
+        };
+        // keep the backtrace of the original error
+        #[cfg(feature="backtrace")]
+        let err = PyErr { rust_backtrace: rust_backtrace, ..err };
+        err
+    }
+
+    /// Normalizes the error and splits it into the exception type, the exception instance
+    /// and the traceback (if any).
+    /// This is the opposite of `from_parts()`.
+    pub fn into_normalized_parts(self, py: Python) -> (PyObject, PyObject, Option<PyObject>) {
+        let PyErr { ptype, pvalue, ptraceback, .. } = self.into_normalized(py);
+        let pvalue = match pvalue {
+            Some(pvalue) => pvalue,
+            None => py.None()
+        };
+        (ptype, pvalue, ptraceback)
     }
 
     /// Retrieves the exception type.
@@ -228,7 +308,7 @@ impl PyErr {
     /// This is the opposite of `PyErr::fetch()`.
     #[inline]
     pub fn restore(self, py: Python) {
-        let PyErr { ptype, pvalue, ptraceback } = self;
+        let PyErr { ptype, pvalue, ptraceback, .. } = self;
         unsafe {
             ffi::PyErr_Restore(ptype.steal_ptr(), pvalue.steal_ptr(py), ptraceback.steal_ptr(py))
         }
@@ -309,8 +389,32 @@ pub fn error_on_minusone(py : Python, result : libc::c_int) -> PyResult<()> {
 
 #[cfg(test)]
 mod tests {
-    use {Python, PyErr};
-    use objects::exc;
+    use {Python, PythonObject, PyErr};
+    use objects::{exc, PyObject};
+    use objectprotocol::ObjectProtocol;
+    use ffi;
+
+    unsafe extern "C" fn len_plus_one(obj: *mut ffi::PyObject) -> *mut ffi::PyObject {
+        let py = Python::assume_gil_acquired();
+        let obj = PyObject::from_borrowed_ptr(py, obj);
+        let len = py_try!(py, obj.len(py));
+        ffi::PyLong_FromSsize_t(len as ffi::Py_ssize_t + 1)
+    }
+
+    #[test]
+    fn py_try_restores_error() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let list = py.eval("[1, 2, 3]", None, None).unwrap();
+        let result = unsafe { PyObject::from_owned_ptr(py, len_plus_one(list.as_ptr())) };
+        assert_eq!(4, result.extract::<i32>(py).unwrap());
+
+        let int = py.eval("42", None, None).unwrap();
+        let result = unsafe { len_plus_one(int.as_ptr()) };
+        assert!(result.is_null());
+        assert!(PyErr::occurred(py));
+        assert!(PyErr::fetch(py).matches(py, py.get_type::<exc::TypeError>().as_object()));
+    }
 
     #[test]
     fn set_typeerror() {
@@ -320,6 +424,48 @@ mod tests {
         assert!(PyErr::occurred(py));
         drop(PyErr::fetch(py));
     }
-}
 
+    #[test]
+    fn normalized_parts_round_trip() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let err = py.run("raise ValueError('test')", None, None).unwrap_err();
+        let (ptype, pvalue, ptraceback) = err.into_normalized_parts(py);
+        assert!(ptype == *py.get_type::<exc::ValueError>().as_object());
+        assert!(ptraceback.is_some());
+        let value_ptr = pvalue.as_ptr();
+
+        PyErr::from_parts(ptype, Some(pvalue), ptraceback).restore(py);
+        let mut err = PyErr::fetch(py);
+        assert!(err.matches(py, py.get_type::<exc::ValueError>().as_object()));
+        assert_eq!(value_ptr, err.instance(py).as_ptr());
+    }
 
+    #[test]
+    #[cfg(feature="backtrace")]
+    fn rust_backtrace_captured() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut err = PyErr::new::<exc::ValueError, _>(py, "test");
+        assert!(!err.rust_backtrace().frames().is_empty());
+        err.normalize(py);
+        assert!(!err.rust_backtrace().frames().is_empty());
+    }
+
+    #[test]
+    fn from_instance_preserves_instance() {
+        use {PyDict, PyClosure, PyClone};
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let d = PyDict::new(py);
+        py.run("e = ValueError('bad value')\ne.code = 42", None, Some(&d)).unwrap();
+        let instance = d.get_item(py, "e").unwrap();
+        let raise = PyClosure::new(py, move |py, _args, _kwargs| {
+            Err(PyErr::from_instance(py, instance.clone_ref(py)))
+        });
+        d.set_item(py, "raise_it", raise).unwrap();
+        py.run("try:\n    raise_it()\nexcept ValueError as caught:\n    \
+                assert caught is e\n    result = caught.code", None, Some(&d)).unwrap();
+        assert_eq!(42, d.get_item(py, "result").unwrap().extract::<i32>(py).unwrap());
+    }
+}
@@ -16,10 +16,15 @@
 // OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-use std::ptr;
-use python::{Python, PythonObject};
-use objects::{PyObject, PyTuple, PyDict, PyString, exc};
+use std::{mem, ptr};
+use std::cell::Cell;
+use std::sync::{Once, ONCE_INIT};
+use libc;
+use abort_on_panic::PanicGuard;
+use python::{Python, PythonObject, PyDrop};
+use objects::{PyObject, PyTuple, PyDict, PyString, PyModule, exc};
 use conversion::ToPyObject;
+use objectprotocol::ObjectProtocol;
 use ffi;
 use err::{self, PyResult};
 
@@ -122,6 +127,52 @@ macro_rules! py_fn {
     }});
 }
 
+/// Adds multiple Rust functions to a Python module, wrapping each with `py_fn!()`.
+///
+/// Syntax: `py_module_add_functions!(py, m, [ f, g(x: i32), h() ])`
+///
+/// * `py`: the `Python` token
+/// * `m`: expression of type `&PyModule`
+/// * A list of functions in any of the forms accepted by `py_fn!()`.
+///   Each function is added to the module under its own name.
+///
+/// Expands to an expression of type `PyResult<()>`.
+/// If the module already has an attribute with the same name as one of the functions,
+/// a `ValueError` is returned and the remaining functions are not added.
+///
+/// # Example
+/// ```
+/// #![feature(plugin)]
+/// #![plugin(interpolate_idents)]
+/// #[macro_use] extern crate cpython;
+/// use cpython::{Python, PyResult, PyModule};
+///
+/// fn add(_py: Python, a: i32, b: i32) -> PyResult<i32> { Ok(a + b) }
+/// fn answer(_py: Python) -> PyResult<i32> { Ok(42) }
+///
+/// fn main() {
+///     let gil = Python::acquire_gil();
+///     let py = gil.python();
+///     let m = PyModule::new(py, "example").unwrap();
+///     py_module_add_functions!(py, &m, [ add(a: i32, b: i32), answer() ]).unwrap();
+/// }
+/// ```
+#[macro_export]
+macro_rules! py_module_add_functions {
+    ($py:expr, $m:expr, [ $( $f:ident $( ( $($params:tt)* ) )* ),* ]) => {{
+        let py: $crate::Python = $py;
+        let m: &$crate::PyModule = $m;
+        let mut result: $crate::PyResult<()> = Ok(());
+        $(
+            if result.is_ok() {
+                result = $crate::_detail::py_module_add_fn(py, m, stringify!($f),
+                    py_fn!($f $( ( $($params)* ) )*));
+            }
+        )*
+        result
+    }}
+}
+
 /// Result type of the `py_fn!()` macro.
 ///
 /// Use the `ToPyObject` implementation to create a python callable object.
@@ -142,5 +193,160 @@ impl ToPyObject for PyFn {
     }
 }
 
+/// Adds `f` to the module `m`, failing if the name is already taken.
+#[doc(hidden)]
+pub fn py_module_add_fn(py: Python, m: &PyModule, name: &str, f: PyFn) -> PyResult<()> {
+    if try!(m.as_object().hasattr(py, name)) {
+        return Err(err::PyErr::new::<exc::ValueError, _>(py,
+            format!("module '{}' already has an attribute named '{}'",
+                    try!(m.name(py)), name)));
+    }
+    m.add(py, name, f)
+}
+
+type BoxedClosure = Box<FnMut(Python, &PyTuple, Option<&PyDict>) -> PyResult<PyObject> + Send>;
+
+struct ClosureData {
+    f: BoxedClosure,
+    running: Cell<bool>
+}
+
+/// A Python callable object that invokes a Rust closure.
+///
+/// Unlike `py_fn!()`, which only works with plain functions, the closure may capture state.
+/// The closure is dropped when the Python object is deallocated.
+///
+/// # Example
+/// ```
+/// use cpython::{Python, PyClosure, PyObject, ObjectProtocol, NoArgs, ToPyObject, PythonObject};
+///
+/// let gil = Python::acquire_gil();
+/// let py = gil.python();
+/// let mut count = 0;
+/// let counter = PyClosure::new(py, move |py, _args, _kwargs| {
+///     count += 1;
+///     Ok(count.to_py_object(py).into_object())
+/// });
+/// assert_eq!(1, counter.as_object().call(py, NoArgs, None).unwrap().extract::<i32>(py).unwrap());
+/// assert_eq!(2, counter.as_object().call(py, NoArgs, None).unwrap().extract::<i32>(py).unwrap());
+/// ```
+pub struct PyClosure(PyObject);
+
+static mut CLOSURE_DEF: ffi::PyMethodDef = ffi::PyMethodDef {
+    ml_name: b"closure\0" as *const u8 as *const libc::c_char,
+    // initialized once by PyClosure::new(), as the transmute can't be done in a static initializer
+    ml_meth: None,
+    ml_flags: ffi::METH_VARARGS | ffi::METH_KEYWORDS,
+    ml_doc: 0 as *const libc::c_char
+};
+
+static CLOSURE_DEF_INIT: Once = ONCE_INIT;
+
+unsafe extern "C" fn closure_capsule_destructor(capsule: *mut ffi::PyObject) {
+    let _guard = PanicGuard::with_message("Rust panic in PyClosure destructor");
+    let data = ffi::PyCapsule_GetPointer(capsule, ptr::null()) as *mut ClosureData;
+    drop(Box::from_raw(data));
+}
+
+unsafe extern "C" fn closure_wrapper(
+    capsule: *mut ffi::PyObject,
+    args: *mut ffi::PyObject,
+    kwargs: *mut ffi::PyObject) -> *mut ffi::PyObject
+{
+    let _guard = PanicGuard::with_message("Rust panic in PyClosure");
+    let py = Python::assume_gil_acquired();
+    let data = &mut *(ffi::PyCapsule_GetPointer(capsule, ptr::null()) as *mut ClosureData);
+    if data.running.get() {
+        err::PyErr::new::<exc::RuntimeError, _>(py, "PyClosure called recursively").restore(py);
+        return ptr::null_mut();
+    }
+    let args = PyObject::from_borrowed_ptr(py, args).unchecked_cast_into::<PyTuple>();
+    let kwargs = match PyObject::from_borrowed_ptr_opt(py, kwargs) {
+        Some(kwargs) => Some(kwargs.unchecked_cast_into::<PyDict>()),
+        None => None
+    };
+    data.running.set(true);
+    let ret = (data.f)(py, &args, kwargs.as_ref());
+    data.running.set(false);
+    kwargs.release_ref(py);
+    args.release_ref(py);
+    match ret {
+        Ok(val) => val.steal_ptr(),
+        Err(e) => {
+            e.restore(py);
+            ptr::null_mut()
+        }
+    }
+}
+
+impl PyClosure {
+    /// Creates a new Python callable from the Rust closure.
+    ///
+    /// The closure is called with the positional arguments and the keyword arguments (if any).
+    /// Recursive calls of the closure (via Python code invoked by the closure itself)
+    /// fail with a `RuntimeError`.
+    pub fn new<F>(py: Python, f: F) -> PyClosure
+        where F: FnMut(Python, &PyTuple, Option<&PyDict>) -> PyResult<PyObject> + Send + 'static
+    {
+        let data = Box::new(ClosureData { f: Box::new(f), running: Cell::new(false) });
+        unsafe {
+            let data = Box::into_raw(data) as *mut libc::c_void;
+            let capsule = ffi::PyCapsule_New(data, ptr::null(), Some(closure_capsule_destructor));
+            if capsule.is_null() {
+                drop(Box::from_raw(data as *mut ClosureData));
+                panic!("Out of memory");
+            }
+            let capsule = PyObject::from_owned_ptr(py, capsule);
+            CLOSURE_DEF_INIT.call_once(|| {
+                CLOSURE_DEF.ml_meth = Some(mem::transmute::<ffi::PyCFunctionWithKeywords, ffi::PyCFunction>(closure_wrapper));
+            });
+            let obj = err::from_owned_ptr_or_panic(py,
+                ffi::PyCFunction_NewEx(&mut CLOSURE_DEF, capsule.as_ptr(), ptr::null_mut()));
+            capsule.release_ref(py);
+            PyClosure(obj)
+        }
+    }
+}
+
+impl PythonObject for PyClosure {
+    #[inline]
+    fn as_object(&self) -> &PyObject {
+        &self.0
+    }
+
+    #[inline]
+    fn into_object(self) -> PyObject {
+        self.0
+    }
+
+    /// Unchecked downcast from PyObject to Self.
+    /// Undefined behavior if the input object is not a `PyClosure`.
+    #[inline]
+    unsafe fn unchecked_downcast_from(obj: PyObject) -> PyClosure {
+        PyClosure(obj)
+    }
+
+    /// Unchecked downcast from PyObject to Self.
+    /// Undefined behavior if the input object is not a `PyClosure`.
+    #[inline]
+    unsafe fn unchecked_downcast_borrow_from<'a>(obj: &'a PyObject) -> &'a PyClosure {
+        mem::transmute(obj)
+    }
+}
+
+impl ToPyObject for PyClosure {
+    type ObjectType = PyObject;
+
+    #[inline]
+    fn to_py_object(&self, py: Python) -> PyObject {
+        ::python::PyClone::clone_ref(&self.0, py)
+    }
+
+    #[inline]
+    fn into_py_object(self, _py: Python) -> PyObject {
+        self.0
+    }
+}
+
 // Tests for this file are in tests/test_function.rs
 
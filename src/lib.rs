@@ -79,6 +79,16 @@ extern crate libc;
 
 #[macro_use]
 extern crate abort_on_panic;
+#[cfg(feature="backtrace")]
+extern crate backtrace;
+#[cfg(feature="serde-json")]
+extern crate serde_json;
+#[cfg(feature="uuid")]
+extern crate uuid;
+#[cfg(feature="chrono")]
+extern crate chrono;
+#[cfg(feature="half")]
+extern crate half;
 
 #[cfg(feature="python27-sys")]
 extern crate python27_sys as ffi;
@@ -89,11 +99,13 @@ extern crate python3_sys as ffi;
 pub use ffi::Py_ssize_t;
 pub use err::{PyErr, PyResult};
 pub use objects::*;
-pub use python::{Python, PythonObject, PythonObjectWithCheckedDowncast, PythonObjectWithTypeObject, PyClone, PyDrop};
-pub use pythonrun::{GILGuard, GILProtected, prepare_freethreaded_python};
+pub use python::{Python, CompileMode, PythonObject, PythonObjectWithCheckedDowncast, PythonObjectWithTypeObject, PyClone, PyDrop};
+pub use pythonrun::{GILGuard, GILProtected, BorrowToken, prepare_freethreaded_python};
 pub use conversion::{ExtractPyObject, ToPyObject};
 pub use objectprotocol::{ObjectProtocol};
-pub use rustobject::{PyRustType, PyRustObject};
+pub use buffer::{PyBufferMut, BufferElement};
+pub use function::PyClosure;
+pub use rustobject::{PyRustType, PyRustObject, PyRustMapping, PyRustIterator};
 pub use rustobject::typebuilder::PyRustTypeBuilder;
 
 #[cfg(feature="python27-sys")]
@@ -121,6 +133,7 @@ mod err;
 mod conversion;
 mod objects;
 mod objectprotocol;
+mod buffer;
 mod pythonrun;
 pub mod argparse;
 mod function;
@@ -132,8 +145,8 @@ pub mod _detail {
     pub use ffi;
     pub use libc;
     pub use abort_on_panic::PanicGuard;
-    pub use err::from_owned_ptr_or_panic;
-    pub use function::py_fn_impl;
+    pub use err::{from_owned_ptr_or_panic, result_from_owned_ptr};
+    pub use function::{py_fn_impl, py_module_add_fn};
     pub use rustobject::method::{py_method_impl, py_class_method_impl};
 
     /// assume_gil_acquired(), but the returned Python<'p> is bounded by the scope
@@ -143,6 +156,26 @@ pub mod _detail {
     pub unsafe fn bounded_assume_gil_acquired<'p, T>(_bound: &'p T) -> super::Python<'p> {
         super::Python::assume_gil_acquired()
     }
+
+    /// Used by `py_struct_from_tuple!`.
+    pub fn struct_tuple_length_error(py: super::Python, t: &super::PyTuple,
+                                     expected_length: usize, struct_name: &str) -> super::PyErr {
+        let msg = format!("Expected tuple of length {} for {}, but got tuple of length {}.",
+                          expected_length, struct_name, t.len(py));
+        super::PyErr::new::<super::exc::ValueError, _>(py, msg)
+    }
+
+    /// Used by `py_struct_from_tuple!`.
+    pub fn struct_field_error(py: super::Python, mut err: super::PyErr,
+                              struct_name: &str, field: &str) -> super::PyErr {
+        use super::ObjectProtocol;
+        let msg = match err.instance(py).str(py) {
+            Ok(s) => s.to_string_lossy(py).into_owned(),
+            Err(_) => String::new()
+        };
+        super::PyErr::new::<super::exc::TypeError, _>(py,
+            format!("Failed to extract field '{}' of {}: {}", field, struct_name, msg))
+    }
 }
 
 /// Expands to an `extern "C"` function that allows Python to load
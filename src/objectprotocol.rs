@@ -20,11 +20,63 @@ use std::fmt;
 use std::cmp::Ordering;
 use ffi;
 use libc;
-use python::{Python, PythonObject, ToPythonPointer};
-use objects::{PyObject, PyTuple, PyDict, PyString};
-use conversion::ToPyObject;
+use python::{Python, PythonObject, ToPythonPointer, PyClone};
+use objects::{PyObject, PyTuple, PyDict, PyString, PyBytes};
+use conversion::{ToPyObject, ExtractPyObject};
 use err::{PyErr, PyResult, self};
 
+/// Calls a Python object with positional arguments, without building an argument tuple.
+///
+/// Macro syntax: `py_call!(py, callable, arg1, arg2, ...)`
+///
+/// `callable` and all arguments must implement `PythonObject` (or be references to such objects).
+/// This is equivalent to `callable.call(py, (arg1, arg2, ...), None)`, but uses
+/// `PyObject_CallFunctionObjArgs`, avoiding the conversion of each argument and the allocation
+/// of the argument tuple in Rust. Evaluates to a `PyResult<PyObject>`.
+///
+/// # Example
+/// ```
+/// #[macro_use] extern crate cpython;
+/// use cpython::Python;
+///
+/// fn main() {
+///     let gil = Python::acquire_gil();
+///     let py = gil.python();
+///     let max = py.eval("max", None, None).unwrap();
+///     let a = py.eval("1", None, None).unwrap();
+///     let b = py.eval("2", None, None).unwrap();
+///     let result = py_call!(py, max, a, b).unwrap();
+///     assert_eq!(2, result.extract::<i32>(py).unwrap());
+/// }
+/// ```
+#[macro_export]
+macro_rules! py_call {
+    // Binds each argument to a local variable (hygiene keeps the `arg`s distinct),
+    // so that only the FFI call itself ends up in the `unsafe` block.
+    (@bind $py: ident, $callable: ident, [$($bound: ident)*], $arg: expr $(, $rest: expr)*) => {{
+        let arg = &$arg;
+        py_call!(@bind $py, $callable, [$($bound)* arg] $(, $rest)*)
+    }};
+    (@bind $py: ident, $callable: ident, [$($bound: ident)*]) => {{
+        #[allow(unused_imports)]
+        use $crate::PythonObject;
+        let callable = $callable.as_object().as_ptr();
+        $(let $bound = $bound.as_object().as_ptr();)*
+        unsafe {
+            $crate::_detail::result_from_owned_ptr($py,
+                $crate::_detail::ffi::PyObject_CallFunctionObjArgs(
+                    callable, $($bound,)* ::std::ptr::null_mut::<$crate::_detail::ffi::PyObject>()))
+        }
+    }};
+    ($py: expr, $callable: expr $(, $arg: expr)*) => {{
+        let py: $crate::Python = $py;
+        let callable = &$callable;
+        py_call!(@bind py, callable, [] $(, $arg)*)
+    }};
+}
+
+static mut SYS_MODULE: *mut ffi::PyObject = 0 as *mut ffi::PyObject;
+
 /// Trait that contains methods 
 pub trait ObjectProtocol : PythonObject {
     /// Determines whether this object has the given attribute.
@@ -46,6 +98,24 @@ pub trait ObjectProtocol : PythonObject {
         })
     }
 
+    /// Retrieves an attribute value, returning `None` if the attribute does not exist.
+    /// This is equivalent to the Python expression 'getattr(self, attr_name, None)',
+    /// except that an attribute whose value is `None` is returned as `Some`.
+    ///
+    /// Errors other than `AttributeError` raised while looking up the attribute are propagated.
+    fn getattr_opt<N>(&self, py: Python, attr_name: N) -> PyResult<Option<PyObject>> where N: ToPyObject {
+        match self.getattr(py, attr_name) {
+            Ok(attr) => Ok(Some(attr)),
+            Err(e) => {
+                if e.matches(py, py.get_type::<::objects::exc::AttributeError>().as_object()) {
+                    Ok(None)
+                } else {
+                    Err(e)
+                }
+            }
+        }
+    }
+
     /// Sets an attribute value.
     /// This is equivalent to the Python expression 'self.attr_name = value'.
     #[inline]
@@ -115,6 +185,29 @@ pub trait ObjectProtocol : PythonObject {
         }
     }
 
+    /// Converts self to a byte string.
+    /// This is equivalent to the Python expression 'bytes(self)'.
+    ///
+    /// As with the Python constructor, objects implementing `__bytes__` or the
+    /// buffer protocol are converted, and an integer produces that many zero bytes.
+    /// On Python 2, `bytes` is an alias for `str`.
+    #[inline]
+    fn bytes(&self, py: Python) -> PyResult<PyBytes> {
+        let bytes_type = py.get_type::<PyBytes>();
+        let result = try!(bytes_type.as_object().call(py, (self.as_object(),), None));
+        Ok(try!(result.cast_into::<PyBytes>(py)))
+    }
+
+    /// Formats the object according to the format specification `spec`.
+    /// This is equivalent to the Python expression `format(self, spec)`.
+    /// An empty `spec` gives the same result as `str(self)`.
+    #[inline]
+    fn format_spec(&self, py: Python, spec: &str) -> PyResult<PyString> {
+        spec.with_borrowed_ptr(py, |spec| unsafe {
+            err::result_cast_from_owned_ptr(py, ffi::PyObject_Format(self.as_ptr(), spec))
+        })
+    }
+
     /// Determines whether this object is callable.
     #[inline]
     fn is_callable(&self, _py: Python) -> bool {
@@ -123,6 +216,48 @@ pub trait ObjectProtocol : PythonObject {
         }
     }
 
+    /// Determines whether this object has a callable attribute with the given name.
+    /// Returns `false` if the attribute is missing or is not callable.
+    ///
+    /// Errors other than `AttributeError` raised while looking up the attribute are propagated.
+    fn has_method(&self, py: Python, name: &str) -> PyResult<bool> {
+        Ok(match try!(self.getattr_opt(py, name)) {
+            Some(attr) => attr.is_callable(py),
+            None => false
+        })
+    }
+
+    /// Returns the `__dict__` of the object, like the Python expression `vars(self)`.
+    /// For modules, this is the module namespace.
+    ///
+    /// Raises `TypeError` if the object has no `__dict__`, or if its `__dict__`
+    /// is not an actual `dict` (e.g. the read-only mapping proxy of a type object).
+    fn vars(&self, py: Python) -> PyResult<PyDict> {
+        let dict = match self.getattr(py, "__dict__") {
+            Ok(dict) => dict,
+            Err(e) => {
+                if e.matches(py, py.get_type::<::objects::exc::AttributeError>().as_object()) {
+                    return Err(PyErr::new::<::objects::exc::TypeError, _>(py,
+                        "vars() argument must have __dict__ attribute"));
+                }
+                return Err(e);
+            }
+        };
+        match dict.cast_into::<PyDict>(py) {
+            Ok(dict) => Ok(dict),
+            Err(_) => Err(PyErr::new::<::objects::exc::TypeError, _>(py, "__dict__ is not a dict"))
+        }
+    }
+
+    /// Returns the size of the object in bytes, as reported by `sys.getsizeof(self)`.
+    ///
+    /// This honors `__sizeof__` and includes the garbage collector overhead,
+    /// but not the size of objects referenced by this object.
+    fn sizeof(&self, py: Python) -> PyResult<usize> {
+        let sys = try!(unsafe { ::python::import_cached(py, &mut SYS_MODULE, "sys") });
+        try!(sys.call(py, "getsizeof", (self.as_object(),), None)).extract(py)
+    }
+
     /// Calls the object.
     /// This is equivalent to the Python expression: 'self(*args, **kwargs)'
     #[inline]
@@ -143,6 +278,56 @@ pub trait ObjectProtocol : PythonObject {
         try!(self.getattr(py, name)).call(py, args, kwargs)
     }
 
+    /// Calls a method on the object and extracts the result.
+    /// This is equivalent to `self.call_method(py, name, args, kwargs)`
+    /// followed by `extract::<T>(py)` on the result.
+    #[inline]
+    fn call_method_extract<A, T>(&self, py: Python, name: &str, args: A, kwargs: Option<&PyDict>) -> PyResult<T>
+        where A: ToPyObject<ObjectType=PyTuple>, T: for<'prep> ExtractPyObject<'prep>
+    {
+        try!(self.call_method(py, name, args, kwargs)).extract(py)
+    }
+
+    /// Calls a method on the object and returns an iterator over the result.
+    /// This is equivalent to the Python expression: 'iter(self.name(*args, **kwargs))'
+    ///
+    /// The items are produced lazily; no intermediate list is created.
+    #[inline]
+    fn call_method_iter<'p, A>(&self, py: Python<'p>, name: &str, args: A, kwargs: Option<&PyDict>)
+        -> PyResult<::objects::PyIterator<'p>>
+        where A: ToPyObject<ObjectType=PyTuple>
+    {
+        try!(self.call_method(py, name, args, kwargs)).iter(py)
+    }
+
+    /// Calls the object, like `call()`, but attaches `ctx` to the exception if the call fails.
+    ///
+    /// On Python 3.11 and later, `ctx` is added to the original exception via `add_note()`.
+    /// On older versions, the exception is wrapped in a `RuntimeError` whose message
+    /// starts with `ctx` (on Python 3, the original exception is kept as `__cause__`).
+    fn call_ctx<A>(&self, py: Python, args: A, kwargs: Option<&PyDict>, ctx: &str) -> PyResult<PyObject>
+        where A: ToPyObject<ObjectType=PyTuple>
+    {
+        let mut e = match self.call(py, args, kwargs) {
+            Ok(result) => return Ok(result),
+            Err(e) => e
+        };
+        let instance = e.instance(py);
+        if try!(instance.has_method(py, "add_note")) {
+            try!(instance.call_method(py, "add_note", (ctx,), None));
+            return Err(e);
+        }
+        let msg = match instance.str(py) {
+            Ok(s) => format!("{}: {}", ctx, s.to_string_lossy(py)),
+            Err(_) => ctx.to_owned()
+        };
+        let mut wrapped = PyErr::new::<::objects::exc::RuntimeError, _>(py, msg);
+        if cfg!(feature="python3-sys") {
+            try!(wrapped.instance(py).setattr(py, "__cause__", instance));
+        }
+        Err(wrapped)
+    }
+
     /// Retrieves the hash code of the object.
     /// This is equivalent to the Python expression: 'hash(self)'
     #[inline]
@@ -167,6 +352,43 @@ pub trait ObjectProtocol : PythonObject {
         }
     }
 
+    /// Computes the absolute value of the object.
+    /// This is equivalent to the Python expression `abs(self)`.
+    #[inline]
+    fn abs(&self, py: Python) -> PyResult<PyObject> {
+        unsafe {
+            err::result_from_owned_ptr(py, ffi::PyNumber_Absolute(self.as_ptr()))
+        }
+    }
+
+    /// Computes the negation of the object.
+    /// This is equivalent to the Python expression `-self`.
+    #[inline]
+    fn neg(&self, py: Python) -> PyResult<PyObject> {
+        unsafe {
+            err::result_from_owned_ptr(py, ffi::PyNumber_Negative(self.as_ptr()))
+        }
+    }
+
+    /// Applies the unary plus operator to the object.
+    /// This is equivalent to the Python expression `+self`.
+    #[inline]
+    fn pos(&self, py: Python) -> PyResult<PyObject> {
+        unsafe {
+            err::result_from_owned_ptr(py, ffi::PyNumber_Positive(self.as_ptr()))
+        }
+    }
+
+    /// Computes the quotient and remainder of dividing self by `other`.
+    /// This is equivalent to the Python expression `divmod(self, other)`.
+    fn divmod<O>(&self, py: Python, other: O) -> PyResult<(PyObject, PyObject)> where O: ToPyObject {
+        let result: PyTuple = try!(other.with_borrowed_ptr(py, |other| unsafe {
+            err::result_cast_from_owned_ptr(py, ffi::PyNumber_Divmod(self.as_ptr(), other))
+        }));
+        let items: [PyObject; 2] = try!(result.unpack(py));
+        Ok((items[0].clone_ref(py), items[1].clone_ref(py)))
+    }
+
     /// Returns the length of the sequence or mapping.
     /// This is equivalent to the Python expression: 'len(self)'
     #[inline]
@@ -180,9 +402,15 @@ pub trait ObjectProtocol : PythonObject {
     }
 
     /// This is equivalent to the Python expression: 'self[key]'
+    ///
+    /// Indexing a `list` with an in-range `int` avoids the generic `PyObject_GetItem` call.
     #[inline]
     fn get_item<K>(&self, py: Python, key: K) -> PyResult<PyObject> where K: ToPyObject {
         key.with_borrowed_ptr(py, |key| unsafe {
+            let item = list_item_fast_path(self.as_ptr(), key);
+            if !item.is_null() {
+                return Ok(PyObject::from_borrowed_ptr(py, item));
+            }
             err::result_from_owned_ptr(py,
                 ffi::PyObject_GetItem(self.as_ptr(), key))
         })
@@ -219,10 +447,166 @@ pub trait ObjectProtocol : PythonObject {
         });
         Ok(try!(::objects::PyIterator::from_object(py, obj)))
     }
+
+    /// Returns an iterator over the items of self in reverse order.
+    /// This is equivalent to the Python expression `reversed(self)`.
+    ///
+    /// Uses `__reversed__` if available, otherwise the sequence protocol
+    /// (`__len__` and `__getitem__`); raises `TypeError` for other objects.
+    fn reversed<'p>(&self, py: Python<'p>) -> PyResult<::objects::PyIterator<'p>> {
+        let obj = try!(unsafe {
+            let reversed_type = &mut ffi::PyReversed_Type as *mut ffi::PyTypeObject as *mut ffi::PyObject;
+            err::result_from_owned_ptr(py,
+                ffi::PyObject_CallFunctionObjArgs(reversed_type, self.as_ptr(), ::std::ptr::null_mut::<ffi::PyObject>()))
+        });
+        Ok(try!(::objects::PyIterator::from_object(py, obj)))
+    }
+
+    /// Runs the awaitable object (e.g. a coroutine) to completion
+    /// and returns its result.
+    ///
+    /// This blocks the current thread, running the awaitable via
+    /// `asyncio.get_event_loop().run_until_complete(self)`.
+    /// If the current thread has no event loop, a new one is created.
+    /// Raises `TypeError` if the object is not awaitable.
+    #[cfg(feature="python3-sys")]
+    fn run_coroutine(&self, py: Python) -> PyResult<PyObject> {
+        static mut ASYNCIO: *mut ffi::PyObject = 0 as *mut ffi::PyObject;
+        let asyncio = try!(unsafe { ::python::import_cached(py, &mut ASYNCIO, "asyncio") });
+        let event_loop = match asyncio.call(py, "get_event_loop", ::objects::NoArgs, None) {
+            Ok(event_loop) => event_loop,
+            Err(_) => {
+                let event_loop = try!(asyncio.call(py, "new_event_loop", ::objects::NoArgs, None));
+                try!(asyncio.call(py, "set_event_loop", (&event_loop,), None));
+                event_loop
+            }
+        };
+        event_loop.call_method(py, "run_until_complete", (self.as_object(),), None)
+    }
+
+    /// Enters the runtime context of this context manager.
+    /// This is equivalent to the Python expression `self.__enter__()`.
+    #[inline]
+    fn enter(&self, py: Python) -> PyResult<PyObject> {
+        self.call_method(py, "__enter__", ::objects::NoArgs, None)
+    }
+
+    /// Exits the runtime context of this context manager.
+    /// Pass `None` for all three arguments if no exception occurred.
+    ///
+    /// Returns `true` if the context manager wants to suppress the exception.
+    /// This is equivalent to the Python expression `self.__exit__(exc_type, exc_val, tb)`.
+    fn exit(&self, py: Python, exc_type: &PyObject, exc_val: &PyObject, tb: &PyObject) -> PyResult<bool> {
+        try!(self.call_method(py, "__exit__", (exc_type, exc_val, tb), None)).is_true(py)
+    }
+
+    /// Runs `f` within the runtime context of this context manager,
+    /// similar to the Python `with` statement.
+    /// `f` receives the value returned by `__enter__()`.
+    ///
+    /// If `f` fails, the exception is passed to `__exit__()`.
+    /// If `__exit__()` suppresses the exception, `Ok(None)` is returned.
+    fn with_context<F, R>(&self, py: Python, f: F) -> PyResult<Option<R>>
+        where F: FnOnce(PyObject) -> PyResult<R>
+    {
+        let value = try!(self.enter(py));
+        match f(value) {
+            Ok(result) => {
+                let none = py.None();
+                try!(self.exit(py, &none, &none, &none));
+                Ok(Some(result))
+            },
+            Err(mut e) => {
+                let instance = e.instance(py);
+                let ptype = e.ptype.clone_ref(py);
+                let tb = match e.ptraceback {
+                    Some(ref tb) => tb.clone_ref(py),
+                    None => py.None()
+                };
+                if try!(self.exit(py, &ptype, &instance, &tb)) {
+                    Ok(None)
+                } else {
+                    Err(e)
+                }
+            }
+        }
+    }
+
+    /// Returns the pickling information for the object.
+    /// This is equivalent to the Python expression `self.__reduce_ex__(2)`,
+    /// falling back to `self.__reduce__()` if the object does not define `__reduce_ex__`.
+    ///
+    /// Use `PyType::newobj()` to invoke `copyreg.__newobj__` when
+    /// reconstructing objects from the result.
+    fn reduce(&self, py: Python) -> PyResult<PyObject> {
+        if try!(self.has_method(py, "__reduce_ex__")) {
+            self.call_method(py, "__reduce_ex__", (2,), None)
+        } else {
+            self.call_method(py, "__reduce__", ::objects::NoArgs, None)
+        }
+    }
+
+    /// Creates a `memoryview` over the object, which must support the buffer protocol.
+    /// This is equivalent to the Python expression `memoryview(self)`.
+    ///
+    /// The memoryview shares the memory of the object; slicing it
+    /// (e.g. using `PySequence::get_slice`) does not copy any data.
+    #[inline]
+    fn memoryview(&self, py: Python) -> PyResult<PyObject> {
+        unsafe {
+            err::result_from_owned_ptr(py, ffi::PyMemoryView_FromObject(self.as_ptr()))
+        }
+    }
+
+    /// Requests a writable, C-contiguous buffer from the object.
+    /// Fails if the object does not support the buffer protocol or is read-only.
+    #[inline]
+    fn get_buffer_mut(&self, py: Python) -> PyResult<::buffer::PyBufferMut> {
+        ::buffer::PyBufferMut::get(py, self.as_object())
+    }
 }
 
 impl ObjectProtocol for PyObject {}
 
+/// Returns the borrowed item `obj[key]` if `obj` is a `list` and `key` is an `int` within range
+/// (negative indices count from the end).
+/// Returns null in all other cases, including errors, leaving them to `PyObject_GetItem`.
+#[cfg(feature="python27-sys")]
+unsafe fn list_item_fast_path(obj: *mut ffi::PyObject, key: *mut ffi::PyObject) -> *mut ffi::PyObject {
+    if ffi::PyList_CheckExact(obj) == 0 || ffi::PyInt_CheckExact(key) == 0 {
+        return ::std::ptr::null_mut();
+    }
+    list_item_at(obj, ffi::PyInt_AS_LONG(key) as ffi::Py_ssize_t)
+}
+
+/// Returns the borrowed item `obj[key]` if `obj` is a `list` and `key` is an `int` within range
+/// (negative indices count from the end).
+/// Returns null in all other cases, including errors, leaving them to `PyObject_GetItem`.
+#[cfg(feature="python3-sys")]
+unsafe fn list_item_fast_path(obj: *mut ffi::PyObject, key: *mut ffi::PyObject) -> *mut ffi::PyObject {
+    if ffi::PyList_CheckExact(obj) == 0 || ffi::PyLong_CheckExact(key) == 0 {
+        return ::std::ptr::null_mut();
+    }
+    let index = ffi::PyLong_AsSsize_t(key);
+    if index == -1 && !ffi::PyErr_Occurred().is_null() {
+        // overflow: PyObject_GetItem raises the appropriate error
+        ffi::PyErr_Clear();
+        return ::std::ptr::null_mut();
+    }
+    list_item_at(obj, index)
+}
+
+#[inline]
+unsafe fn list_item_at(list: *mut ffi::PyObject, index: ffi::Py_ssize_t) -> *mut ffi::PyObject {
+    let len = ffi::PyList_Size(list);
+    let index = if index < 0 { index + len } else { index };
+    if index < 0 || index >= len {
+        ::std::ptr::null_mut()
+    } else {
+        ffi::PyList_GetItem(list, index)
+    }
+}
+
 impl fmt::Debug for PyObject {
     fn fmt(&self, f : &mut fmt::Formatter) -> Result<(), fmt::Error> {
         // TODO: we shouldn't use fmt::Error when repr() fails
@@ -248,7 +632,9 @@ mod test {
     use std;
     use python::{Python, PythonObject};
     use conversion::ToPyObject;
-    use objects::{PyList, PyTuple};
+    use objects::{PyDict, PyList, PyTuple};
+    use err::PyResult;
+    use super::ObjectProtocol;
 
     #[test]
     fn test_debug_string() {
@@ -265,5 +651,339 @@ mod test {
         let v = "Hello\n".to_py_object(py).into_object();
         assert_eq!(format!("{}", v), "Hello\n");
     }
-}
 
+    #[test]
+    #[cfg(feature="python3-sys")]
+    fn test_run_coroutine() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let d = PyDict::new(py);
+        py.run("async def answer():\n    return 42\n", None, Some(&d)).unwrap();
+        let coro = py.eval("answer()", None, Some(&d)).unwrap();
+        assert_eq!(42, coro.run_coroutine(py).unwrap().extract::<i32>(py).unwrap());
+        assert!(42.to_py_object(py).into_object().run_coroutine(py).is_err());
+    }
+
+    #[test]
+    fn test_getattr_opt() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let d = PyDict::new(py);
+        py.run("class C(object):\n    x = 42\n    none = None\n\
+                \x20   @property\n    def broken(self):\n        raise ValueError('broken')\n",
+               None, Some(&d)).unwrap();
+        let obj = py.eval("C()", None, Some(&d)).unwrap();
+        assert_eq!(42, obj.getattr_opt(py, "x").unwrap().unwrap().extract::<i32>(py).unwrap());
+        assert!(obj.getattr_opt(py, "none").unwrap().unwrap() == py.None());
+        assert!(obj.getattr_opt(py, "missing").unwrap().is_none());
+        let err = obj.getattr_opt(py, "broken").err().unwrap();
+        assert!(err.matches(py, py.get_type::<::objects::exc::ValueError>().as_object()));
+    }
+
+    #[test]
+    fn test_py_call() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let d = PyDict::new(py);
+        py.run("def f(a, b):\n    return a * 10 + b\n", None, Some(&d)).unwrap();
+        let f = d.get_item(py, "f").unwrap();
+        let a = 4.to_py_object(py);
+        let b = 2.to_py_object(py);
+        let fast = py_call!(py, f, a, &b).unwrap();
+        let slow = f.call(py, (&a, &b), None).unwrap();
+        assert_eq!(42, fast.extract::<i32>(py).unwrap());
+        assert_eq!(slow.extract::<i32>(py).unwrap(), fast.extract::<i32>(py).unwrap());
+        assert_eq!(7, py_call!(py, py.eval("lambda: 7", None, None).unwrap()).unwrap().extract::<i32>(py).unwrap());
+        assert!(py_call!(py, f, a).is_err());
+    }
+
+    #[test]
+    fn test_get_item_list_fast_path() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let list = PyList::new(py, &[10.to_py_object(py).into_object(),
+                                     20.to_py_object(py).into_object(),
+                                     30.to_py_object(py).into_object()]).into_object();
+        let tuple = py.eval("(10, 20, 30)", None, None).unwrap();
+        for &index in &[0i64, 2, -1, -3] {
+            assert_eq!(tuple.get_item(py, index).unwrap().extract::<i32>(py).unwrap(),
+                       list.get_item(py, index).unwrap().extract::<i32>(py).unwrap());
+        }
+        for &index in &[3i64, -4, ::std::i64::MAX, ::std::i64::MIN] {
+            let err = list.get_item(py, index).err().unwrap();
+            assert!(err.matches(py, py.get_type::<::objects::exc::IndexError>().as_object()));
+        }
+        let big = py.eval("10 ** 30", None, None).unwrap();
+        let err = list.get_item(py, big).err().unwrap();
+        assert!(err.matches(py, py.get_type::<::objects::exc::IndexError>().as_object()));
+        assert!(list.get_item(py, "x").is_err());
+    }
+
+    #[test]
+    fn test_is_callable() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let d = PyDict::new(py);
+        py.run("def f():\n    pass\n\nclass C(object):\n    pass\n", None, Some(&d)).unwrap();
+        assert!(py.eval("f", None, Some(&d)).unwrap().is_callable(py));
+        assert!(py.eval("C", None, Some(&d)).unwrap().is_callable(py));
+        assert!(!py.eval("C()", None, Some(&d)).unwrap().is_callable(py));
+        let int = 42.to_py_object(py).into_object();
+        assert!(!int.is_callable(py));
+
+        let mut err = int.call(py, ::objects::NoArgs, None).err().unwrap();
+        let msg: String = err.instance(py).str(py).unwrap().into_object().extract(py).unwrap();
+        assert_eq!("'int' object is not callable", msg);
+    }
+
+    #[test]
+    fn test_has_method() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let d = PyDict::new(py);
+        py.run("class WithMethod(object):\n    def close(self): pass\n\n\
+                class WithData(object):\n    close = 42\n", None, Some(&d)).unwrap();
+        let obj = py.eval("WithMethod()", None, Some(&d)).unwrap();
+        assert!(obj.has_method(py, "close").unwrap());
+        let obj = py.eval("WithData()", None, Some(&d)).unwrap();
+        assert!(!obj.has_method(py, "close").unwrap());
+        let obj = py.eval("object()", None, Some(&d)).unwrap();
+        assert!(!obj.has_method(py, "close").unwrap());
+    }
+
+    #[test]
+    fn test_with_context() {
+        use err::PyErr;
+        use objects::exc;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let d = PyDict::new(py);
+        py.run("class Tracker(object):\n\
+                \x20   def __init__(self, suppress):\n\
+                \x20       self.log = []\n\
+                \x20       self.suppress = suppress\n\
+                \x20   def __enter__(self):\n\
+                \x20       self.log.append('enter')\n\
+                \x20       return 42\n\
+                \x20   def __exit__(self, ty, val, tb):\n\
+                \x20       self.log.append('exit' if ty is None else 'exit ' + ty.__name__)\n\
+                \x20       return self.suppress\n", None, Some(&d)).unwrap();
+
+        let cm = py.eval("Tracker(False)", None, Some(&d)).unwrap();
+        let result = cm.with_context(py, |value| value.extract::<i32>(py)).unwrap();
+        assert_eq!(Some(42), result);
+        d.set_item(py, "cm", &cm).unwrap();
+        assert!(py.eval("cm.log == ['enter', 'exit']", None, Some(&d)).unwrap().is_true(py).unwrap());
+
+        let cm = py.eval("Tracker(False)", None, Some(&d)).unwrap();
+        let result: PyResult<Option<()>> = cm.with_context(py, |_| Err(PyErr::new::<exc::ValueError, _>(py, "oops")));
+        assert!(result.is_err());
+        d.set_item(py, "cm", &cm).unwrap();
+        assert!(py.eval("cm.log == ['enter', 'exit ValueError']", None, Some(&d)).unwrap().is_true(py).unwrap());
+
+        let cm = py.eval("Tracker(True)", None, Some(&d)).unwrap();
+        let result: PyResult<Option<()>> = cm.with_context(py, |_| Err(PyErr::new::<exc::ValueError, _>(py, "oops")));
+        assert!(result.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_reduce() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let d = PyDict::new(py);
+        py.run("class Simple(object):\n    pass\n", None, Some(&d)).unwrap();
+        let obj = py.eval("Simple()", None, Some(&d)).unwrap();
+        let reduced = obj.reduce(py).unwrap().cast_into::<PyTuple>(py).unwrap();
+        assert!(reduced.len(py) >= 2);
+        assert!(reduced.get_item(py, 0).is_callable(py));
+    }
+
+    #[test]
+    fn test_call_ctx() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let d = PyDict::new(py);
+        py.run("def fail():\n    raise ValueError('inner')\n", None, Some(&d)).unwrap();
+        let fail = py.eval("fail", None, Some(&d)).unwrap();
+        let mut err = fail.call_ctx(py, ::objects::NoArgs, None, "while loading config").err().unwrap();
+        d.set_item(py, "e", err.instance(py)).unwrap();
+        let text: String = py.eval("'\\n'.join([str(e)] + list(getattr(e, '__notes__', [])))",
+                                   None, Some(&d)).unwrap().extract(py).unwrap();
+        assert!(text.contains("while loading config"));
+        assert!(text.contains("inner"));
+
+        let len = py.eval("len", None, None).unwrap();
+        assert_eq!(3, len.call_ctx(py, ("abc",), None, "unused").unwrap().extract::<i32>(py).unwrap());
+    }
+
+    #[test]
+    fn test_vars() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let sys = py.import("sys").unwrap();
+        let vars = sys.as_object().vars(py).unwrap();
+        assert!(vars.get_item(py, "path").is_some());
+
+        let d = PyDict::new(py);
+        py.run("class Point(object):\n\
+                \x20   def __init__(self):\n\
+                \x20       self.x = 1\n\
+                \x20       self.y = 2\n", None, Some(&d)).unwrap();
+        let obj = py.eval("Point()", None, Some(&d)).unwrap();
+        let vars = obj.vars(py).unwrap();
+        assert_eq!(2, vars.len(py));
+        assert_eq!(1, vars.get_item(py, "x").unwrap().extract::<i32>(py).unwrap());
+
+        assert!(42.to_py_object(py).into_object().vars(py).is_err());
+    }
+
+    #[test]
+    fn test_memoryview() {
+        use objects::PySequence;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let bytes = py.eval("b'hello world'", None, None).unwrap();
+        let view = bytes.memoryview(py).unwrap();
+        assert_eq!(11, view.len(py).unwrap());
+        let chunk = view.cast_into::<PySequence>(py).unwrap().get_slice(py, 6, 11).unwrap();
+        assert_eq!(5, chunk.len(py).unwrap());
+        let d = PyDict::new(py);
+        d.set_item(py, "chunk", chunk).unwrap();
+        assert!(py.eval("chunk.tobytes() == b'world'", None, Some(&d)).unwrap().is_true(py).unwrap());
+
+        assert!(42.to_py_object(py).into_object().memoryview(py).is_err());
+    }
+
+    #[test]
+    fn test_unary_ops() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let v = (-5).to_py_object(py).into_object();
+        assert_eq!(5, v.abs(py).unwrap().extract::<i32>(py).unwrap());
+        assert_eq!(5, v.neg(py).unwrap().extract::<i32>(py).unwrap());
+        assert_eq!(-5, v.pos(py).unwrap().extract::<i32>(py).unwrap());
+
+        let d = PyDict::new(py);
+        py.run("class Vec2(object):\n\
+                \x20   def __abs__(self):\n\
+                \x20       return 5.0\n", None, Some(&d)).unwrap();
+        let obj = py.eval("Vec2()", None, Some(&d)).unwrap();
+        assert_eq!(5.0, obj.abs(py).unwrap().extract::<f64>(py).unwrap());
+        assert!(obj.neg(py).is_err());
+        assert!("abc".to_py_object(py).into_object().abs(py).is_err());
+    }
+
+    #[test]
+    fn test_reversed() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let list = vec![1, 2, 3].to_py_object(py).into_object();
+        let items: Vec<i32> = list.reversed(py).unwrap().map(|x| x.unwrap().extract(py).unwrap()).collect();
+        assert_eq!(vec![3, 2, 1], items);
+        let err = 5.to_py_object(py).into_object().reversed(py).err().unwrap();
+        assert!(err.matches(py, py.get_type::<::objects::exc::TypeError>().as_object()));
+    }
+
+    #[test]
+    fn test_divmod() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let v = 17.to_py_object(py).into_object();
+        let (q, r) = v.divmod(py, 5).unwrap();
+        assert_eq!((3, 2), (q.extract::<i32>(py).unwrap(), r.extract::<i32>(py).unwrap()));
+        let err = v.divmod(py, 0).unwrap_err();
+        assert!(err.matches(py, py.get_type::<::objects::exc::ZeroDivisionError>().as_object()));
+    }
+
+    #[test]
+    fn test_call_method() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let list = vec![1, 2].to_py_object(py).into_object();
+        let ret = list.call_method(py, "append", (3,), None).unwrap();
+        assert!(ret == py.None());
+        assert_eq!(vec![1, 2, 3], list.extract::<Vec<i32>>(py).unwrap());
+        let args = PyTuple::new(py, &[4.to_py_object(py).into_object()]);
+        list.call_method(py, "append", args, None).unwrap();
+        assert_eq!(4, list.len(py).unwrap());
+        assert!(list.call_method(py, "missing", ::objects::NoArgs, None).is_err());
+    }
+
+    #[test]
+    fn test_call_method_extract() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let d = PyDict::new(py);
+        py.run("class Calc(object):\n\
+                \x20   def divmod(self, a, b):\n\
+                \x20       return (a // b, a % b)\n", None, Some(&d)).unwrap();
+        let calc = py.eval("Calc()", None, Some(&d)).unwrap();
+        let (q, r): (i64, i64) = calc.call_method_extract(py, "divmod", (17, 5), None).unwrap();
+        assert_eq!((3, 2), (q, r));
+        assert!(calc.call_method_extract::<_, (i64, i64, i64)>(py, "divmod", (17, 5), None).is_err());
+        assert!(calc.call_method_extract::<_, String>(py, "divmod", (17, 5), None).is_err());
+    }
+
+    #[test]
+    fn test_call_method_iter() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let d = PyDict::new(py);
+        py.run("class Counter(object):\n\
+                \x20   def count(self, n):\n\
+                \x20       for i in range(n):\n\
+                \x20           yield i * i\n", None, Some(&d)).unwrap();
+        let obj = py.eval("Counter()", None, Some(&d)).unwrap();
+        let squares: Vec<i32> = obj.call_method_iter(py, "count", (4,), None).unwrap()
+            .map(|item| item.unwrap().extract::<i32>(py).unwrap())
+            .collect();
+        assert_eq!(vec![0, 1, 4, 9], squares);
+        assert!(obj.call_method_iter(py, "missing", ::objects::NoArgs, None).is_err());
+    }
+
+    #[test]
+    fn test_sizeof() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let small = 1.to_py_object(py).into_object().sizeof(py).unwrap();
+        let list = py.eval("list(range(1000))", None, None).unwrap().sizeof(py).unwrap();
+        assert!(small > 0);
+        assert!(list > small);
+        assert!(list >= 1000 * ::std::mem::size_of::<usize>());
+    }
+
+    #[test]
+    fn test_format_spec() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let f = 3.14159.to_py_object(py).into_object();
+        let s: String = f.format_spec(py, ".2f").unwrap().into_object().extract(py).unwrap();
+        assert_eq!("3.14", s);
+        let i = 255.to_py_object(py).into_object();
+        let s: String = i.format_spec(py, "x").unwrap().into_object().extract(py).unwrap();
+        assert_eq!("ff", s);
+        let s: String = i.format_spec(py, "").unwrap().into_object().extract(py).unwrap();
+        let expected: String = i.str(py).unwrap().into_object().extract(py).unwrap();
+        assert_eq!(expected, s);
+    }
+
+    #[test]
+    fn test_bytes() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = py.eval("bytearray(b'abc')", None, None).unwrap();
+        assert_eq!(&b"abc"[..], obj.bytes(py).unwrap().as_slice(py));
+    }
+
+    #[test]
+    #[cfg(feature="python3-sys")]
+    fn test_bytes_python3() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = 3.to_py_object(py).into_object();
+        assert_eq!(&b"\0\0\0"[..], obj.bytes(py).unwrap().as_slice(py));
+        let obj = "abc".to_py_object(py).into_object();
+        let err = obj.bytes(py).unwrap_err();
+        assert!(err.matches(py, py.get_type::<::objects::exc::TypeError>().as_object()));
+    }
+}
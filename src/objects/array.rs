@@ -0,0 +1,177 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Conversions between slices of primitive numbers and Python `array.array` objects.
+
+use std::{mem, ptr, slice};
+use ffi;
+use python::{self, Python};
+use err::{PyErr, PyResult};
+use conversion::ToPyObject;
+use objectprotocol::ObjectProtocol;
+use buffer::BufferElement;
+use super::{exc, PyObject, PyBytes, PyModule};
+
+static mut ARRAY_MODULE: *mut ffi::PyObject = 0 as *mut ffi::PyObject;
+
+fn array_module(py: Python) -> PyResult<PyModule> {
+    unsafe { python::import_cached(py, &mut ARRAY_MODULE, "array") }
+}
+
+#[cfg(feature="python27-sys")]
+const TOBYTES: &'static str = "tostring";
+#[cfg(feature="python3-sys")]
+const TOBYTES: &'static str = "tobytes";
+
+/// Element types that can be stored in a Python `array.array`.
+pub unsafe trait ArrayElement : BufferElement {
+    /// The `array.array` typecode for this element type.
+    fn typecode() -> &'static str;
+}
+
+macro_rules! array_element(
+    ($t:ty, $typecode:expr) => (
+        unsafe impl ArrayElement for $t {
+            #[inline]
+            fn typecode() -> &'static str { $typecode }
+        }
+    )
+);
+
+array_element!(i8, "b");
+array_element!(u8, "B");
+array_element!(i16, "h");
+array_element!(u16, "H");
+array_element!(i32, "i");
+array_element!(u32, "I");
+array_element!(f32, "f");
+array_element!(f64, "d");
+
+/// Wrapper that converts a slice of numbers to a Python `array.array`
+/// with the matching typecode, e.g. `'d'` for `f64`.
+///
+/// For large numeric data, this is more memory-efficient than a list.
+pub struct ArraySlice<'a, T: 'a>(pub &'a [T]);
+
+/// Wrapper for extracting a Python `array.array` into a `Vec`.
+///
+/// Extraction fails with `TypeError` if the object is not an `array.array`
+/// or its typecode does not match the element type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArrayVec<T>(pub Vec<T>);
+
+/// Converts to an `array.array` containing a copy of the slice.
+///
+/// Panics if the Python `array` module cannot be imported.
+impl <'a, T> ToPyObject for ArraySlice<'a, T> where T: ArrayElement {
+    type ObjectType = PyObject;
+
+    fn to_py_object(&self, py: Python) -> PyObject {
+        let bytes = unsafe {
+            slice::from_raw_parts(self.0.as_ptr() as *const u8, self.0.len() * mem::size_of::<T>())
+        };
+        let bytes = PyBytes::new(py, bytes);
+        array_module(py)
+            .and_then(|m| m.call(py, "array", (T::typecode(), bytes), None))
+            .expect("failed to create array.array")
+    }
+}
+
+impl <T> ToPyObject for ArrayVec<T> where T: ArrayElement {
+    type ObjectType = PyObject;
+
+    #[inline]
+    fn to_py_object(&self, py: Python) -> PyObject {
+        ArraySlice(&self.0).to_py_object(py)
+    }
+}
+
+impl <'prepared, T> ::conversion::ExtractPyObject<'prepared> for ArrayVec<T> where T: ArrayElement {
+    type Prepared = PyObject;
+
+    #[inline]
+    fn prepare_extract(py: Python, obj: &PyObject) -> PyResult<Self::Prepared> {
+        Ok(::python::PyClone::clone_ref(obj, py))
+    }
+
+    fn extract(py: Python, obj: &'prepared PyObject) -> PyResult<ArrayVec<T>> {
+        let array_type = try!(try!(array_module(py)).get(py, "array"));
+        let is_array = unsafe { ffi::PyObject_IsInstance(obj.as_ptr(), array_type.as_ptr()) };
+        if is_array == -1 {
+            return Err(PyErr::fetch(py));
+        } else if is_array == 0 {
+            return Err(PyErr::new::<exc::TypeError, _>(py,
+                format!("expected array.array, got {}", obj.get_type().name(py))));
+        }
+        let typecode: String = try!(try!(obj.getattr(py, "typecode")).extract(py));
+        if typecode != T::typecode() {
+            return Err(PyErr::new::<exc::TypeError, _>(py,
+                format!("expected array.array with typecode '{}', got '{}'", T::typecode(), typecode)));
+        }
+        let bytes = try!(try!(obj.call_method(py, TOBYTES, super::NoArgs, None)).cast_into::<PyBytes>(py));
+        let bytes = bytes.as_slice(py);
+        let len = bytes.len() / mem::size_of::<T>();
+        let mut v = Vec::with_capacity(len);
+        unsafe {
+            ptr::copy_nonoverlapping(bytes.as_ptr(), v.as_mut_ptr() as *mut u8, len * mem::size_of::<T>());
+            v.set_len(len);
+        }
+        Ok(ArrayVec(v))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use python::{Python, PythonObject};
+    use conversion::ToPyObject;
+    use objectprotocol::ObjectProtocol;
+    use objects::exc;
+    use super::{ArraySlice, ArrayVec};
+
+    #[test]
+    fn test_round_trip_f64() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let data = [1.5, -2.25, 0.0, 1e300];
+        let obj = ArraySlice(&data).to_py_object(py);
+        let typecode: String = obj.getattr(py, "typecode").unwrap().extract(py).unwrap();
+        assert_eq!("d", typecode);
+        assert_eq!(4, obj.len(py).unwrap());
+        assert_eq!(-2.25, obj.get_item(py, 1).unwrap().extract::<f64>(py).unwrap());
+        assert_eq!(ArrayVec(data.to_vec()), obj.extract::<ArrayVec<f64>>(py).unwrap());
+    }
+
+    #[test]
+    fn test_round_trip_i32() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = ArrayVec(vec![1i32, -2, 3]).to_py_object(py);
+        assert_eq!(vec![1, -2, 3], obj.extract::<ArrayVec<i32>>(py).unwrap().0);
+    }
+
+    #[test]
+    fn test_extract_errors() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = ArraySlice(&[1.0f64]).to_py_object(py);
+        let err = obj.extract::<ArrayVec<i32>>(py).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::TypeError>().as_object()));
+        let list = vec![1.0f64].to_py_object(py).into_object();
+        assert!(list.extract::<ArrayVec<f64>>(py).is_err());
+    }
+}
@@ -0,0 +1,141 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::{ptr, slice};
+use libc::c_char;
+use ffi;
+use python::{Python, ToPythonPointer};
+use super::PyObject;
+use err::{self, PyResult};
+
+/// Represents a Python `bytearray`: a mutable sequence of bytes.
+pub struct PyByteArray(PyObject);
+
+pyobject_newtype!(PyByteArray, PyByteArray_Check, PyByteArray_Type);
+
+impl PyByteArray {
+    /// Creates a new Python bytearray object.
+    /// The bytearray is initialized by copying the data from the `&[u8]`.
+    ///
+    /// Panics if out of memory.
+    pub fn new(py: Python, src: &[u8]) -> PyByteArray {
+        let ptr = src.as_ptr() as *const c_char;
+        let len = src.len() as ffi::Py_ssize_t;
+        unsafe {
+            err::cast_from_owned_ptr_or_panic(py,
+                ffi::PyByteArray_FromStringAndSize(ptr, len))
+        }
+    }
+
+    /// Gets the length of the bytearray.
+    #[inline]
+    pub fn len(&self, _py: Python) -> usize {
+        unsafe { ffi::PyByteArray_Size(self.as_ptr()) as usize }
+    }
+
+    /// Gets the contents of the bytearray as byte slice.
+    ///
+    /// This is unsafe because the slice is invalidated if the bytearray is resized
+    /// (by `resize()`, or by Python code running while the slice is alive),
+    /// and because the caller must ensure that no mutable slice into the same bytearray
+    /// (from `as_mut_slice()`) is alive at the same time.
+    /// Use `to_vec()` for a safe copy of the contents.
+    pub unsafe fn data(&self, _py: Python) -> &[u8] {
+        let buffer = ffi::PyByteArray_AsString(self.as_ptr()) as *const u8;
+        let length = ffi::PyByteArray_Size(self.as_ptr()) as usize;
+        slice::from_raw_parts(buffer, length)
+    }
+
+    /// Copies the contents of the bytearray into a new `Vec<u8>`.
+    pub fn to_vec(&self, py: Python) -> Vec<u8> {
+        unsafe { self.data(py) }.to_vec()
+    }
+
+    /// Gets the contents of the bytearray as mutable byte slice,
+    /// allowing the data to be modified in place without copying.
+    ///
+    /// This is unsafe because the slice is invalidated if the bytearray is resized
+    /// (e.g. by Python code running while the slice is alive),
+    /// and because the caller must ensure that no other slices into the same bytearray
+    /// (from `data()` or `as_mut_slice()`) are alive at the same time.
+    pub unsafe fn as_mut_slice(&self, _py: Python) -> &mut [u8] {
+        let buffer = ffi::PyByteArray_AsString(self.as_ptr()) as *mut u8;
+        let length = ffi::PyByteArray_Size(self.as_ptr()) as usize;
+        slice::from_raw_parts_mut(buffer, length)
+    }
+
+    /// Resizes the bytearray to `len` bytes.
+    /// If the bytearray grows, the new bytes are set to zero.
+    pub fn resize(&self, py: Python, len: usize) -> PyResult<()> {
+        let old_len = self.len(py);
+        unsafe {
+            try!(err::error_on_minusone(py,
+                ffi::PyByteArray_Resize(self.as_ptr(), len as ffi::Py_ssize_t)));
+            if len > old_len {
+                let buffer = ffi::PyByteArray_AsString(self.as_ptr()) as *mut u8;
+                ptr::write_bytes(buffer.offset(old_len as isize), 0, len - old_len);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use python::{Python, PythonObject};
+    use objects::PyDict;
+    use super::PyByteArray;
+
+    #[test]
+    fn test_new() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let ba = PyByteArray::new(py, b"hello");
+        assert_eq!(5, ba.len(py));
+        assert_eq!(b"hello", unsafe { ba.data(py) });
+        assert_eq!(b"hello".to_vec(), ba.to_vec(py));
+    }
+
+    #[test]
+    fn test_resize() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let ba = PyByteArray::new(py, b"abc");
+        ba.resize(py, 6).unwrap();
+        assert_eq!(vec![b'a', b'b', b'c', 0, 0, 0], ba.to_vec(py));
+        ba.resize(py, 2).unwrap();
+        assert_eq!(b"ab".to_vec(), ba.to_vec(py));
+        ba.resize(py, 4).unwrap();
+        assert_eq!(vec![b'a', b'b', 0, 0], ba.to_vec(py));
+    }
+
+    #[test]
+    fn test_fill_pattern() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let ba = PyByteArray::new(py, &[0; 8]);
+        for (i, b) in unsafe { ba.as_mut_slice(py) }.iter_mut().enumerate() {
+            *b = if i % 2 == 0 { 0xAA } else { 0x55 };
+        }
+        let d = PyDict::new(py);
+        d.set_item(py, "ba", ba.as_object()).unwrap();
+        let ok: bool = py.eval("ba == bytearray(b'\\xaa\\x55' * 4)", None, Some(&d))
+            .unwrap().extract(py).unwrap();
+        assert!(ok);
+    }
+}
@@ -0,0 +1,197 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Conversions between `chrono` date/time types and Python `datetime.datetime` objects.
+//! Requires the `chrono` feature.
+//!
+//! Naive and timezone-aware values are never mixed up silently:
+//!
+//! * `NaiveDateTime` converts to and from a naive `datetime` (`tzinfo is None`).
+//!   Extracting it from an aware `datetime` fails with `ValueError`.
+//! * `DateTime<UTC>` can only be extracted from an aware `datetime`; the value is
+//!   normalized to UTC using its `utcoffset()`. Extracting it from a naive `datetime`
+//!   fails with `ValueError`.
+//!
+//! Python's `datetime` has microsecond resolution, so sub-microsecond precision is truncated
+//! when converting to Python. A chrono leap second is clamped to the last microsecond
+//! of the preceding second.
+
+use chrono::{NaiveDate, NaiveTime, NaiveDateTime, DateTime, UTC, Datelike, Timelike, Duration};
+use ffi;
+use python::{self, Python, PythonObject};
+use err::{PyErr, PyResult};
+use conversion::ToPyObject;
+use objectprotocol::ObjectProtocol;
+use super::{exc, PyObject, PyModule, NoArgs};
+
+static mut DATETIME_MODULE: *mut ffi::PyObject = 0 as *mut ffi::PyObject;
+
+fn datetime_module(py: Python) -> PyResult<PyModule> {
+    unsafe { python::import_cached(py, &mut DATETIME_MODULE, "datetime") }
+}
+
+/// Reads the calendar fields of a `datetime.datetime` instance, ignoring `tzinfo`.
+fn naive_fields(py: Python, obj: &PyObject) -> PyResult<NaiveDateTime> {
+    let class = try!(try!(datetime_module(py)).get(py, "datetime"));
+    let is_datetime = unsafe { ffi::PyObject_IsInstance(obj.as_ptr(), class.as_ptr()) };
+    if is_datetime == -1 {
+        return Err(PyErr::fetch(py));
+    } else if is_datetime == 0 {
+        return Err(PyErr::new::<exc::TypeError, _>(py,
+            format!("expected datetime.datetime, got {}", obj.get_type().name(py))));
+    }
+    let field = |name: &str| -> PyResult<u32> { try!(obj.getattr(py, name)).extract(py) };
+    let year: i32 = try!(try!(obj.getattr(py, "year")).extract(py));
+    let date = NaiveDate::from_ymd_opt(year, try!(field("month")), try!(field("day")));
+    let time = NaiveTime::from_hms_micro_opt(try!(field("hour")), try!(field("minute")),
+        try!(field("second")), try!(field("microsecond")));
+    match (date, time) {
+        (Some(date), Some(time)) => Ok(NaiveDateTime::new(date, time)),
+        _ => Err(PyErr::new::<exc::ValueError, _>(py, "datetime out of range"))
+    }
+}
+
+fn new_datetime(py: Python, dt: &NaiveDateTime, tzinfo: PyObject) -> PyResult<PyObject> {
+    // Leap seconds are represented by chrono as nanosecond >= 1_000_000_000.
+    let micro = ::std::cmp::min(dt.nanosecond() / 1000, 999_999);
+    try!(datetime_module(py)).call(py, "datetime",
+        (dt.year(), dt.month(), dt.day(), dt.hour(), dt.minute(), dt.second(), micro, tzinfo),
+        None)
+}
+
+/// Converts a `NaiveDateTime` to a naive Python `datetime.datetime`.
+///
+/// Panics if the Python `datetime` module cannot be imported, or if the year
+/// is outside the range supported by Python (1 to 9999).
+impl ToPyObject for NaiveDateTime {
+    type ObjectType = PyObject;
+
+    fn to_py_object(&self, py: Python) -> PyObject {
+        new_datetime(py, self, py.None()).expect("failed to create datetime.datetime")
+    }
+}
+
+/// Converts a `DateTime<UTC>` to an aware Python `datetime.datetime`
+/// with `tzinfo=datetime.timezone.utc`.
+///
+/// Panics if the Python `datetime` module cannot be imported, or if the year
+/// is outside the range supported by Python (1 to 9999).
+#[cfg(feature="python3-sys")]
+impl ToPyObject for DateTime<UTC> {
+    type ObjectType = PyObject;
+
+    fn to_py_object(&self, py: Python) -> PyObject {
+        datetime_module(py)
+            .and_then(|m| m.get(py, "timezone"))
+            .and_then(|tz| tz.getattr(py, "utc"))
+            .and_then(|utc| new_datetime(py, &self.naive_utc(), utc))
+            .expect("failed to create datetime.datetime")
+    }
+}
+
+/// Extracts a `NaiveDateTime` from a naive Python `datetime.datetime`.
+extract!(obj to NaiveDateTime; py => {
+    let dt = try!(naive_fields(py, obj));
+    if try!(obj.getattr(py, "tzinfo")) != py.None() {
+        return Err(PyErr::new::<exc::ValueError, _>(py,
+            "expected a naive datetime, got a timezone-aware datetime"));
+    }
+    Ok(dt)
+});
+
+/// Extracts a `DateTime<UTC>` from a timezone-aware Python `datetime.datetime`,
+/// converting it to UTC.
+extract!(obj to DateTime<UTC>; py => {
+    let dt = try!(naive_fields(py, obj));
+    let offset = try!(obj.call_method(py, "utcoffset", NoArgs, None));
+    if offset == py.None() {
+        return Err(PyErr::new::<exc::ValueError, _>(py,
+            "expected a timezone-aware datetime, got a naive datetime"));
+    }
+    let days: i64 = try!(try!(offset.getattr(py, "days")).extract(py));
+    let seconds: i64 = try!(try!(offset.getattr(py, "seconds")).extract(py));
+    let micros: i64 = try!(try!(offset.getattr(py, "microseconds")).extract(py));
+    let offset = Duration::days(days) + Duration::seconds(seconds) + Duration::microseconds(micros);
+    Ok(DateTime::from_utc(dt - offset, UTC))
+});
+
+#[cfg(test)]
+mod test {
+    use chrono::{NaiveDate, NaiveDateTime, DateTime, UTC};
+    use python::{Python, PythonObject};
+    use conversion::ToPyObject;
+    use objectprotocol::ObjectProtocol;
+
+    fn sample() -> NaiveDateTime {
+        NaiveDate::from_ymd(2016, 2, 29).and_hms_micro(13, 45, 30, 123456)
+    }
+
+    #[test]
+    fn test_naive_round_trip() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = sample().to_py_object(py);
+        let s: String = obj.str(py).unwrap().into_object().extract(py).unwrap();
+        assert_eq!("2016-02-29 13:45:30.123456", s);
+        assert_eq!(sample(), obj.extract::<NaiveDateTime>(py).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature="python3-sys")]
+    fn test_naive_rejects_aware() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = py.eval("__import__('datetime').datetime(2016, 2, 29, 13, 45, 30, 123456, \
+                           __import__('datetime').timezone.utc)", None, None).unwrap();
+        assert!(obj.extract::<NaiveDateTime>(py).is_err());
+        assert_eq!(DateTime::from_utc(sample(), UTC), obj.extract::<DateTime<UTC>>(py).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature="python3-sys")]
+    fn test_utc_round_trip() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dt = DateTime::from_utc(sample(), UTC);
+        let obj = dt.to_py_object(py);
+        let s: String = obj.str(py).unwrap().into_object().extract(py).unwrap();
+        assert_eq!("2016-02-29 13:45:30.123456+00:00", s);
+        assert_eq!(dt, obj.extract::<DateTime<UTC>>(py).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature="python3-sys")]
+    fn test_utc_normalizes_offset() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = py.eval("__import__('datetime').datetime(2016, 2, 29, 15, 45, 30, 123456, \
+                           __import__('datetime').timezone(__import__('datetime').timedelta(hours=2)))",
+                          None, None).unwrap();
+        assert_eq!(DateTime::from_utc(sample(), UTC), obj.extract::<DateTime<UTC>>(py).unwrap());
+    }
+
+    #[test]
+    fn test_utc_rejects_naive() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = sample().to_py_object(py);
+        assert!(obj.extract::<DateTime<UTC>>(py).is_err());
+        let obj = "2016-02-29".to_py_object(py).into_object();
+        assert!(obj.extract::<NaiveDateTime>(py).is_err());
+    }
+}
@@ -19,7 +19,8 @@
 use ffi;
 use python::{Python, PythonObject};
 use conversion::ToPyObject;
-use objects::{PyObject, PyList};
+use objectprotocol::ObjectProtocol;
+use objects::{exc, PyObject, PyList};
 use err::{self, PyResult, PyErr};
 use std::{mem, collections, hash, cmp};
 
@@ -38,7 +39,22 @@ impl PyDict {
         }
     }
 
+    /// Creates a builder for constructing a new dictionary incrementally.
+    ///
+    /// ```
+    /// # use cpython::{Python, PyDict};
+    /// let gil = Python::acquire_gil();
+    /// let py = gil.python();
+    /// let dict = PyDict::builder(py).set("a", 1).set("b", 2).finish().unwrap();
+    /// assert_eq!(2, dict.len(py));
+    /// ```
+    #[inline]
+    pub fn builder<'p>(py: Python<'p>) -> PyDictBuilder<'p> {
+        PyDictBuilder { dict: PyDict::new(py), err: None, py: py }
+    }
+
     /// Return a new dictionary that contains the same key-value pairs as self.
+    /// This is a shallow copy: the values are shared with the original dictionary.
     /// Corresponds to `dict(self)` in Python.
     pub fn copy(&self, py: Python) -> PyResult<PyDict> {
         unsafe {
@@ -90,6 +106,105 @@ impl PyDict {
             }))
     }
 
+    /// Gets an item from the dictionary, or computes and inserts it if it is not present.
+    /// `f` is only called when the key is missing.
+    ///
+    /// The key is converted to a Python object only once.
+    /// Where the interpreter supports it (Python 3.5+, outside the limited API),
+    /// the key is also hashed only once for both the lookup and the insertion.
+    ///
+    /// On Python 3, errors raised while looking up the key (e.g. from `__eq__`)
+    /// are propagated. On Python 2, `PyDict_GetItem` suppresses such errors,
+    /// so the key is treated as missing.
+    pub fn get_or_insert_with<K, F>(&self, py: Python, key: K, f: F) -> PyResult<PyObject>
+        where K: ToPyObject, F: FnOnce() -> PyResult<PyObject>
+    {
+        key.with_borrowed_ptr(py, |key| unsafe {
+            self.get_or_insert_with_ptr(py, key, f)
+        })
+    }
+
+    #[cfg(all(py_sys_config="Py_3_5", not(py_sys_config="Py_LIMITED_API")))]
+    unsafe fn get_or_insert_with_ptr<F>(&self, py: Python, key: *mut ffi::PyObject, f: F) -> PyResult<PyObject>
+        where F: FnOnce() -> PyResult<PyObject>
+    {
+        let hash = ffi::PyObject_Hash(key);
+        if hash == -1 {
+            return Err(PyErr::fetch(py));
+        }
+        let value = ffi::_PyDict_GetItem_KnownHash(self.0.as_ptr(), key, hash);
+        if !value.is_null() {
+            return Ok(PyObject::from_borrowed_ptr(py, value));
+        }
+        if PyErr::occurred(py) {
+            return Err(PyErr::fetch(py));
+        }
+        let value = try!(f());
+        try!(err::error_on_minusone(py,
+            ffi::_PyDict_SetItem_KnownHash(self.0.as_ptr(), key, value.as_ptr(), hash)));
+        Ok(value)
+    }
+
+    #[cfg(all(feature="python3-sys", not(all(py_sys_config="Py_3_5", not(py_sys_config="Py_LIMITED_API")))))]
+    unsafe fn get_or_insert_with_ptr<F>(&self, py: Python, key: *mut ffi::PyObject, f: F) -> PyResult<PyObject>
+        where F: FnOnce() -> PyResult<PyObject>
+    {
+        let value = ffi::PyDict_GetItemWithError(self.0.as_ptr(), key);
+        if !value.is_null() {
+            return Ok(PyObject::from_borrowed_ptr(py, value));
+        }
+        if PyErr::occurred(py) {
+            return Err(PyErr::fetch(py));
+        }
+        let value = try!(f());
+        try!(err::error_on_minusone(py,
+            ffi::PyDict_SetItem(self.0.as_ptr(), key, value.as_ptr())));
+        Ok(value)
+    }
+
+    #[cfg(feature="python27-sys")]
+    unsafe fn get_or_insert_with_ptr<F>(&self, py: Python, key: *mut ffi::PyObject, f: F) -> PyResult<PyObject>
+        where F: FnOnce() -> PyResult<PyObject>
+    {
+        if let Some(value) = PyObject::from_borrowed_ptr_opt(py,
+                ffi::PyDict_GetItem(self.0.as_ptr(), key)) {
+            return Ok(value);
+        }
+        let value = try!(f());
+        try!(err::error_on_minusone(py,
+            ffi::PyDict_SetItem(self.0.as_ptr(), key, value.as_ptr())));
+        Ok(value)
+    }
+
+    /// Gets an item from the dictionary, inserting `default` if the key is not present.
+    /// This is equivalent to the Python expression `self.setdefault(key, default)`:
+    /// an existing value is returned unchanged and is never overwritten.
+    #[cfg(all(py_sys_config="Py_3_4", not(py_sys_config="Py_LIMITED_API")))]
+    pub fn setdefault<K, V>(&self, py: Python, key: K, default: V) -> PyResult<PyObject>
+        where K: ToPyObject, V: ToPyObject
+    {
+        key.with_borrowed_ptr(py, move |key|
+            default.with_borrowed_ptr(py, |default| unsafe {
+                // PyDict_SetDefault returns a borrowed reference
+                let value = ffi::PyDict_SetDefault(self.0.as_ptr(), key, default);
+                if value.is_null() {
+                    Err(PyErr::fetch(py))
+                } else {
+                    Ok(PyObject::from_borrowed_ptr(py, value))
+                }
+            }))
+    }
+
+    /// Gets an item from the dictionary, inserting `default` if the key is not present.
+    /// This is equivalent to the Python expression `self.setdefault(key, default)`:
+    /// an existing value is returned unchanged and is never overwritten.
+    #[cfg(not(all(py_sys_config="Py_3_4", not(py_sys_config="Py_LIMITED_API"))))]
+    pub fn setdefault<K, V>(&self, py: Python, key: K, default: V) -> PyResult<PyObject>
+        where K: ToPyObject, V: ToPyObject
+    {
+        self.as_object().call_method(py, "setdefault", (key, default), None)
+    }
+
     /// Deletes an item.
     /// This is equivalent to the Python expression `del self[key]`.
     pub fn del_item<K>(&self, py: Python, key: K) -> PyResult<()> where K: ToPyObject {
@@ -99,6 +214,18 @@ impl PyDict {
         })
     }
 
+    /// Removes the item with the given key and returns its value.
+    ///
+    /// This is equivalent to the Python expression `self.pop(key[, default])`:
+    /// if the key is absent, `default` is returned if given,
+    /// otherwise `KeyError` is raised.
+    pub fn pop<K>(&self, py: Python, key: K, default: Option<PyObject>) -> PyResult<PyObject> where K: ToPyObject {
+        match default {
+            Some(default) => self.0.call_method(py, "pop", (key, default), None),
+            None => self.0.call_method(py, "pop", (key,), None)
+        }
+    }
+
     // List of dict items.
     // This is equivalent to the python expression `list(dict.items())`.
     pub fn items_list(&self, py: Python) -> PyList {
@@ -109,20 +236,132 @@ impl PyDict {
 
     /// Returns the list of (key,value) pairs in this dictionary.
     pub fn items(&self, py: Python) -> Vec<(PyObject, PyObject)> {
-        // Note that we don't provide an iterator because
-        // PyDict_Next() is unsafe to use when the dictionary might be changed
-        // by other python code.
-        let mut vec = Vec::with_capacity(self.len(py));
+        // No Python code runs during this iteration, so the dictionary cannot change size.
+        self.iter(py).map(|item| item.unwrap()).collect()
+    }
+
+    /// Returns the list of (key,value) pairs in this dictionary, sorted by key.
+    ///
+    /// Keys are compared using Python's `<` operator, so the result does not
+    /// depend on insertion order.
+    /// Returns an error if any two keys cannot be compared.
+    pub fn items_sorted_by_key(&self, py: Python) -> PyResult<Vec<(PyObject, PyObject)>> {
+        let mut items = self.items(py);
+        let mut error = None;
+        items.sort_by(|a, b| {
+            if error.is_some() {
+                return cmp::Ordering::Equal;
+            }
+            match compare_keys(py, &a.0, &b.0) {
+                Ok(ordering) => ordering,
+                Err(e) => {
+                    error = Some(e);
+                    cmp::Ordering::Equal
+                }
+            }
+        });
+        match error {
+            Some(e) => Err(e),
+            None => Ok(items)
+        }
+    }
+
+    /// Returns an iterator over the (key,value) pairs in this dictionary,
+    /// without creating an intermediate list of items.
+    ///
+    /// The dictionary must not be modified while it is being iterated.
+    /// Like in Python, changing the size of the dictionary during iteration
+    /// is detected: the iterator yields a `RuntimeError` and then stops.
+    /// Other modifications (e.g. replacing a value) may result in items
+    /// being skipped or repeated.
+    #[inline]
+    pub fn iter<'a, 'p>(&'a self, py: Python<'p>) -> PyDictIterator<'a, 'p> {
+        PyDictIterator { py: py, dict: self, pos: 0, len: Some(self.len(py)) }
+    }
+}
+
+/// Builder for constructing a `PyDict` incrementally.
+/// Created by `PyDict::builder()`.
+#[must_use]
+pub struct PyDictBuilder<'p> {
+    dict: PyDict,
+    /// The first error that occurred while inserting items.
+    err: Option<PyErr>,
+    py: Python<'p>
+}
+
+impl <'p> PyDictBuilder<'p> {
+    /// Inserts a key-value pair into the dictionary under construction.
+    ///
+    /// If an error occurs, all further calls are ignored
+    /// and the error is returned from `finish()`.
+    pub fn set<K, V>(mut self, key: K, value: V) -> Self where K: ToPyObject, V: ToPyObject {
+        if self.err.is_none() {
+            if let Err(e) = self.dict.set_item(self.py, key, value) {
+                self.err = Some(e);
+            }
+        }
+        self
+    }
+
+    /// Finishes construction of the dictionary.
+    /// Returns the first error that occurred while inserting items, if any.
+    pub fn finish(self) -> PyResult<PyDict> {
+        match self.err {
+            Some(e) => Err(e),
+            None => Ok(self.dict)
+        }
+    }
+}
+
+fn compare_keys(py: Python, a: &PyObject, b: &PyObject) -> PyResult<cmp::Ordering> {
+    let less = |x: &PyObject, y: &PyObject| -> PyResult<bool> {
+        let r = unsafe { ffi::PyObject_RichCompareBool(x.as_ptr(), y.as_ptr(), ffi::Py_LT) };
+        if r < 0 { Err(PyErr::fetch(py)) } else { Ok(r != 0) }
+    };
+    if try!(less(a, b)) {
+        Ok(cmp::Ordering::Less)
+    } else if try!(less(b, a)) {
+        Ok(cmp::Ordering::Greater)
+    } else {
+        Ok(cmp::Ordering::Equal)
+    }
+}
+
+/// Used by `PyDict::iter()`.
+pub struct PyDictIterator<'a, 'p> {
+    py: Python<'p>,
+    dict: &'a PyDict,
+    pos: ffi::Py_ssize_t,
+    /// The expected size of the dictionary; `None` once the iteration has stopped
+    /// because of a size change.
+    len: Option<usize>
+}
+
+impl <'a, 'p> Iterator for PyDictIterator<'a, 'p> {
+    type Item = PyResult<(PyObject, PyObject)>;
+
+    fn next(&mut self) -> Option<PyResult<(PyObject, PyObject)>> {
+        let py = self.py;
+        match self.len {
+            None => return None,
+            Some(len) if len != self.dict.len(py) => {
+                self.len = None;
+                return Some(Err(PyErr::new::<exc::RuntimeError, _>(py,
+                    "dictionary changed size during iteration")));
+            }
+            Some(_) => {}
+        }
         unsafe {
-            let mut pos = 0;
             let mut key: *mut ffi::PyObject = mem::uninitialized();
             let mut value: *mut ffi::PyObject = mem::uninitialized();
-            while ffi::PyDict_Next(self.0.as_ptr(), &mut pos, &mut key, &mut value) != 0 {
-                vec.push((PyObject::from_borrowed_ptr(py, key),
-                          PyObject::from_borrowed_ptr(py, value)));
+            if ffi::PyDict_Next(self.dict.0.as_ptr(), &mut self.pos, &mut key, &mut value) != 0 {
+                Some(Ok((PyObject::from_borrowed_ptr(py, key),
+                         PyObject::from_borrowed_ptr(py, value))))
+            } else {
+                None
             }
         }
-        vec
     }
 }
 
@@ -160,9 +399,134 @@ impl <K, V> ToPyObject for collections::BTreeMap<K, V>
 mod test {
     use python::{Python, PythonObject};
     use conversion::ToPyObject;
-    use objects::{PyDict, PyTuple};
+    use objects::{PyDict, PyList, PyTuple, exc};
     use std::collections::HashMap;
 
+    #[test]
+    fn test_copy() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dict = PyDict::new(py);
+        let list = PyList::new(py, &[]);
+        dict.set_item(py, 1i32, &list).unwrap();
+        let copy = dict.copy(py).unwrap();
+        copy.set_item(py, 2i32, 42i32).unwrap();
+        assert_eq!(1, dict.len(py));
+        assert_eq!(2, copy.len(py));
+        // values are shared, not copied
+        assert!(copy.get_item(py, 1i32).unwrap() == *list.as_object());
+    }
+
+    #[test]
+    fn test_clear() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut v = HashMap::new();
+        v.insert(7, 32);
+        v.insert(8, 42);
+        let dict = v.to_py_object(py);
+        assert_eq!(2, dict.len(py));
+        dict.clear(py);
+        assert_eq!(0, dict.len(py));
+        assert_eq!(None, dict.get_item(py, 7i32));
+    }
+
+    #[test]
+    fn test_get_or_insert_with() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dict = PyDict::new(py);
+        let mut calls = 0;
+        let v = dict.get_or_insert_with(py, 1i32, || { calls += 1; Ok(42i32.to_py_object(py).into_object()) }).unwrap();
+        assert_eq!(42, v.extract::<i32>(py).unwrap());
+        assert_eq!(1, calls);
+        assert_eq!(42, dict.get_item(py, 1i32).unwrap().extract::<i32>(py).unwrap());
+        let v = dict.get_or_insert_with(py, 1i32, || { calls += 1; Ok(0i32.to_py_object(py).into_object()) }).unwrap();
+        assert_eq!(42, v.extract::<i32>(py).unwrap());
+        assert_eq!(1, calls);
+    }
+
+    #[test]
+    #[cfg(feature="python3-sys")]
+    fn test_get_or_insert_with_eq_error() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let d = PyDict::new(py);
+        py.run("class K(object):\n    def __hash__(self): return 1\n    def __eq__(self, other): raise ValueError('eq')\nk1, k2 = K(), K()", None, Some(&d)).unwrap();
+        let dict = PyDict::new(py);
+        dict.set_item(py, d.get_item(py, "k1").unwrap(), 1).unwrap();
+        let mut calls = 0;
+        let err = dict.get_or_insert_with(py, d.get_item(py, "k2").unwrap(), || { calls += 1; Ok(py.None()) }).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::ValueError>().as_object()));
+        assert_eq!(0, calls);
+        assert_eq!(1, dict.len(py));
+    }
+
+    #[test]
+    fn test_setdefault() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dict = PyDict::new(py);
+        let v = dict.setdefault(py, 1i32, 42i32).unwrap();
+        assert_eq!(42, v.extract::<i32>(py).unwrap());
+        assert_eq!(42, dict.get_item(py, 1i32).unwrap().extract::<i32>(py).unwrap());
+        let v = dict.setdefault(py, 1i32, 0i32).unwrap();
+        assert_eq!(42, v.extract::<i32>(py).unwrap());
+        assert_eq!(42, dict.get_item(py, 1i32).unwrap().extract::<i32>(py).unwrap());
+        assert_eq!(1, dict.len(py));
+    }
+
+    #[test]
+    fn test_setdefault_eq_error() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let d = PyDict::new(py);
+        py.run("class K(object):\n    def __hash__(self): return 1\n    def __eq__(self, other): raise ValueError('eq')\nk1, k2 = K(), K()", None, Some(&d)).unwrap();
+        let dict = PyDict::new(py);
+        dict.set_item(py, d.get_item(py, "k1").unwrap(), 1).unwrap();
+        let err = dict.setdefault(py, d.get_item(py, "k2").unwrap(), 2).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::ValueError>().as_object()));
+        assert_eq!(1, dict.len(py));
+    }
+
+    #[test]
+    fn test_iter() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut v = HashMap::new();
+        v.insert(7, 32);
+        v.insert(8, 42);
+        v.insert(9, 123);
+        let dict = v.to_py_object(py);
+        let mut key_sum = 0;
+        let mut value_sum = 0;
+        for item in dict.iter(py) {
+            let (key, value) = item.unwrap();
+            let key = key.extract::<i32>(py).unwrap();
+            let value = value.extract::<i32>(py).unwrap();
+            assert_eq!(v[&key], value);
+            key_sum += key;
+            value_sum += value;
+        }
+        assert_eq!(7 + 8 + 9, key_sum);
+        assert_eq!(32 + 42 + 123, value_sum);
+    }
+
+    #[test]
+    fn test_iter_size_changed() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dict = PyDict::new(py);
+        dict.set_item(py, 1i32, 1i32).unwrap();
+        let mut it = dict.iter(py);
+        let (key, _) = it.next().unwrap().unwrap();
+        let key = key.extract::<i32>(py).unwrap();
+        dict.set_item(py, key + 1, 1i32).unwrap();
+        let err = it.next().unwrap().unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::RuntimeError>().as_object()));
+        assert!(it.next().is_none());
+    }
+
     #[test]
     fn test_len() {
         let gil = Python::acquire_gil();
@@ -288,4 +652,62 @@ mod test {
         assert_eq!(32 + 42 + 123, value_sum);
     }
 
+    #[test]
+    fn test_items_sorted_by_key() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dict = PyDict::new(py);
+        dict.set_item(py, "pear", 3).unwrap();
+        dict.set_item(py, "apple", 1).unwrap();
+        dict.set_item(py, "orange", 2).unwrap();
+        let items: Vec<(String, i32)> = dict.items_sorted_by_key(py).unwrap().into_iter()
+            .map(|(k, v)| (k.extract(py).unwrap(), v.extract(py).unwrap()))
+            .collect();
+        assert_eq!(vec![("apple".to_owned(), 1), ("orange".to_owned(), 2), ("pear".to_owned(), 3)], items);
+    }
+
+    #[test]
+    #[cfg(feature="python3-sys")]
+    fn test_items_sorted_by_key_unorderable() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dict = PyDict::new(py);
+        dict.set_item(py, "a", 1).unwrap();
+        dict.set_item(py, 2, 2).unwrap();
+        assert!(dict.items_sorted_by_key(py).is_err());
+    }
+
+    #[test]
+    fn test_pop() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dict = PyDict::builder(py).set("a", 1).finish().unwrap();
+        assert_eq!(1, dict.pop(py, "a", None).unwrap().extract::<i32>(py).unwrap());
+        assert_eq!(0, dict.len(py));
+        let default = 5.to_py_object(py).into_object();
+        assert_eq!(5, dict.pop(py, "a", Some(default)).unwrap().extract::<i32>(py).unwrap());
+        let err = dict.pop(py, "a", None).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::KeyError>().as_object()));
+    }
+
+    #[test]
+    fn test_builder() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dict = PyDict::builder(py).set("a", 1).set("b", 2).set("c", 3).finish().unwrap();
+        assert_eq!(3, dict.len(py));
+        assert_eq!(1, dict.get_item(py, "a").unwrap().extract::<i32>(py).unwrap());
+        assert_eq!(2, dict.get_item(py, "b").unwrap().extract::<i32>(py).unwrap());
+        assert_eq!(3, dict.get_item(py, "c").unwrap().extract::<i32>(py).unwrap());
+    }
+
+    #[test]
+    fn test_builder_error() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        // lists are unhashable, so inserting one as a key fails
+        let unhashable = PyList::new(py, &[]);
+        let result = PyDict::builder(py).set("a", 1).set(unhashable, 2).set("c", 3).finish();
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,109 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Conversions between `std::time::Duration` and Python `float` seconds.
+
+use std::time::Duration;
+use std::u64;
+use python::Python;
+use err::{PyErr, PyResult};
+use conversion::ToPyObject;
+use super::{exc, PyObject, PyFloat};
+
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+/// Newtype wrapper that converts a `Duration` to and from a Python `float`
+/// holding the number of seconds, as expected by APIs like `time.sleep()`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DurationSeconds(pub Duration);
+
+/// Converts the duration to a `float` number of seconds.
+/// Very long durations lose sub-second precision.
+impl ToPyObject for DurationSeconds {
+    type ObjectType = PyFloat;
+
+    fn to_py_object(&self, py: Python) -> PyFloat {
+        let secs = self.0.as_secs() as f64 + self.0.subsec_nanos() as f64 / NANOS_PER_SEC as f64;
+        PyFloat::new(py, secs)
+    }
+}
+
+/// Extracts a duration from a number of seconds (`int`, `float`, or any object
+/// accepted by `extract::<f64>()`). The value is rounded to the nearest nanosecond.
+///
+/// Negative values and NaN raise `ValueError`;
+/// values too large for `Duration` saturate at the maximum duration.
+extract!(obj to DurationSeconds, expected "float"; py => {
+    let secs = try!(obj.extract::<f64>(py));
+    if !(secs >= 0.0) {
+        return Err(PyErr::new::<exc::ValueError, _>(py,
+            format!("duration must be a non-negative number of seconds, got {}", secs)));
+    }
+    if secs >= u64::MAX as f64 {
+        return Ok(DurationSeconds(Duration::new(u64::MAX, NANOS_PER_SEC - 1)));
+    }
+    let whole = secs.trunc();
+    let nanos = ((secs - whole) * NANOS_PER_SEC as f64).round() as u32;
+    let duration = if nanos >= NANOS_PER_SEC {
+        Duration::new(whole as u64, 0) + Duration::new(1, 0)
+    } else {
+        Duration::new(whole as u64, nanos)
+    };
+    Ok(DurationSeconds(duration))
+});
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+    use std::u64;
+    use python::{Python, PythonObject};
+    use conversion::ToPyObject;
+    use super::DurationSeconds;
+
+    #[test]
+    fn test_round_trip() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let d = DurationSeconds(Duration::from_millis(1500));
+        let obj = d.to_py_object(py);
+        assert_eq!(1.5, obj.value(py));
+        assert_eq!(d, obj.into_object().extract::<DurationSeconds>(py).unwrap());
+        let obj = 2.to_py_object(py).into_object();
+        assert_eq!(DurationSeconds(Duration::new(2, 0)), obj.extract(py).unwrap());
+    }
+
+    #[test]
+    fn test_extract_invalid() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = (-1.5).to_py_object(py).into_object();
+        assert!(obj.extract::<DurationSeconds>(py).is_err());
+        let obj = py.eval("float('nan')", None, None).unwrap();
+        assert!(obj.extract::<DurationSeconds>(py).is_err());
+        let obj = "1.5".to_py_object(py).into_object();
+        assert!(obj.extract::<DurationSeconds>(py).is_err());
+    }
+
+    #[test]
+    fn test_extract_saturates() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = 1e30.to_py_object(py).into_object();
+        assert_eq!(DurationSeconds(Duration::new(u64::MAX, 999_999_999)), obj.extract(py).unwrap());
+    }
+}
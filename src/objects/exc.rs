@@ -103,6 +103,13 @@ exc_type!(ValueError, PyExc_ValueError);
 exc_type!(WindowsError, PyExc_WindowsError);
 exc_type!(ZeroDivisionError, PyExc_ZeroDivisionError);
 
+exc_type!(Warning, PyExc_Warning);
+exc_type!(UserWarning, PyExc_UserWarning);
+exc_type!(DeprecationWarning, PyExc_DeprecationWarning);
+exc_type!(PendingDeprecationWarning, PyExc_PendingDeprecationWarning);
+exc_type!(RuntimeWarning, PyExc_RuntimeWarning);
+exc_type!(FutureWarning, PyExc_FutureWarning);
+
 exc_type!(UnicodeDecodeError, PyExc_UnicodeDecodeError);
 exc_type!(UnicodeEncodeError, PyExc_UnicodeEncodeError);
 exc_type!(UnicodeTranslateError, PyExc_UnicodeTranslateError);
@@ -0,0 +1,123 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Conversions between `num::BigRational` and Python `fractions.Fraction` objects.
+
+extern crate num;
+
+use self::num::Zero;
+use self::num::bigint::BigInt;
+use self::num::rational::BigRational;
+use std::ffi::CString;
+use std::ptr;
+use ffi;
+use python::{self, Python, PythonObject};
+use err::{self, PyErr, PyResult};
+use conversion::{self, ExtractPyObject, ToPyObject};
+use objectprotocol::ObjectProtocol;
+use super::{exc, PyObject, PyModule, PyInt, PyLong};
+
+static mut FRACTIONS_MODULE: *mut ffi::PyObject = 0 as *mut ffi::PyObject;
+
+fn fractions_module(py: Python) -> PyResult<PyModule> {
+    unsafe { python::import_cached(py, &mut FRACTIONS_MODULE, "fractions") }
+}
+
+fn bigint_to_py_object(py: Python, val: &BigInt) -> PyObject {
+    let s = CString::new(val.to_string()).unwrap();
+    unsafe {
+        err::from_owned_ptr_or_panic(py,
+            ffi::PyLong_FromString(s.as_ptr() as *mut _, ptr::null_mut(), 10))
+    }
+}
+
+fn bigint_from_py_object(py: Python, obj: &PyObject) -> PyResult<BigInt> {
+    if obj.cast_as::<PyInt>(py).is_err() && obj.cast_as::<PyLong>(py).is_err() {
+        let err = PyErr::new_lazy_init(py.get_type::<exc::TypeError>(), None);
+        return Err(conversion::type_error_expected(py, err, obj, "int"));
+    }
+    let s: String = try!(try!(obj.str(py)).into_object().extract(py));
+    s.parse().map_err(|_| PyErr::new::<exc::ValueError, _>(py,
+        format!("invalid integer '{}'", s)))
+}
+
+/// Converts a `BigRational` to a Python `fractions.Fraction` instance.
+///
+/// Panics if the Python `fractions` module cannot be imported.
+impl ToPyObject for BigRational {
+    type ObjectType = PyObject;
+
+    fn to_py_object(&self, py: Python) -> PyObject {
+        let numer = bigint_to_py_object(py, self.numer());
+        let denom = bigint_to_py_object(py, self.denom());
+        fractions_module(py)
+            .and_then(|m| m.call(py, "Fraction", (numer, denom), None))
+            .expect("failed to create fractions.Fraction")
+    }
+}
+
+/// Extracts a `BigRational` from a Python `fractions.Fraction` instance,
+/// or from any other object with integral `numerator` and `denominator` attributes
+/// (this includes Python integers, which convert with denominator 1).
+extract!(obj to BigRational; py => {
+    let numer = try!(bigint_from_py_object(py, &try!(obj.getattr(py, "numerator"))));
+    let denom = try!(bigint_from_py_object(py, &try!(obj.getattr(py, "denominator"))));
+    if denom.is_zero() {
+        return Err(PyErr::new::<exc::ZeroDivisionError, _>(py, "denominator is zero"));
+    }
+    Ok(BigRational::new(numer, denom))
+});
+
+#[cfg(test)]
+mod test {
+    use super::num::FromPrimitive;
+    use super::num::bigint::BigInt;
+    use super::num::rational::BigRational;
+    use python::{Python, PythonObject};
+    use conversion::ToPyObject;
+    use objectprotocol::ObjectProtocol;
+    use objects::exc;
+
+    #[test]
+    fn test_round_trip() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let r = BigRational::new(BigInt::from_i64(1).unwrap(), BigInt::from_i64(3).unwrap());
+        let obj = r.to_py_object(py);
+        let s: String = obj.str(py).unwrap().into_object().extract(py).unwrap();
+        assert_eq!("1/3", s);
+        assert_eq!(r, obj.extract::<BigRational>(py).unwrap());
+    }
+
+    #[test]
+    fn test_extract() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = py.eval("__import__('fractions').Fraction(-10**30, 7)", None, None).unwrap();
+        let r = obj.extract::<BigRational>(py).unwrap();
+        assert_eq!("-1000000000000000000000000000000/7", r.to_string());
+        let obj = 42.to_py_object(py).into_object();
+        let r = obj.extract::<BigRational>(py).unwrap();
+        assert_eq!(BigRational::from_integer(BigInt::from_i64(42).unwrap()), r);
+        let obj = "1/3".to_py_object(py).into_object();
+        assert!(obj.extract::<BigRational>(py).is_err());
+        let obj = py.eval("type('R', (object,), {'numerator': '1', 'denominator': 2})()", None, None).unwrap();
+        let err = obj.extract::<BigRational>(py).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::TypeError>().as_object()));
+    }
+}
@@ -0,0 +1,74 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Conversions between `half::f16` and Python `float` objects.
+//! Requires the `half` feature.
+
+use half::f16;
+use python::Python;
+use err::PyResult;
+use conversion::ToPyObject;
+use super::{PyObject, PyFloat};
+
+/// Converts an `f16` to a Python `float`. The conversion is exact.
+impl ToPyObject for f16 {
+    type ObjectType = PyFloat;
+
+    fn to_py_object(&self, py: Python) -> PyFloat {
+        PyFloat::new(py, self.to_f64())
+    }
+}
+
+/// Extracts an `f16` from a Python `float` (or any object accepted by `extract::<f64>()`).
+///
+/// Values that are not exactly representable are rounded to the nearest `f16`
+/// (ties to even), following IEEE 754: values beyond the `f16` range become infinity,
+/// tiny values become subnormals or zero, and NaN stays NaN.
+extract!(obj to f16, expected "float"; py => {
+    Ok(f16::from_f64(try!(obj.extract::<f64>(py))))
+});
+
+#[cfg(test)]
+mod test {
+    use half::f16;
+    use python::{Python, PythonObject};
+    use conversion::ToPyObject;
+
+    #[test]
+    fn test_round_trip() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let v = f16::from_f64(1.5);
+        let obj = v.to_py_object(py);
+        assert_eq!(1.5, obj.value(py));
+        assert_eq!(v, obj.into_object().extract::<f16>(py).unwrap());
+    }
+
+    #[test]
+    fn test_rounding() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        // 0.1 is not representable; the nearest f16 is 0.0999755859375
+        let obj = 0.1f64.to_py_object(py).into_object();
+        assert_eq!(0.0999755859375, obj.extract::<f16>(py).unwrap().to_f64());
+        let obj = 1e6f64.to_py_object(py).into_object();
+        assert!(obj.extract::<f16>(py).unwrap().to_f64().is_infinite());
+        let obj = "1.5".to_py_object(py).into_object();
+        assert!(obj.extract::<f16>(py).is_err());
+    }
+}
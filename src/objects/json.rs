@@ -0,0 +1,153 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Conversions between `serde_json::Value` and native Python objects.
+//! Requires the `serde-json` feature.
+
+use std::collections::BTreeMap;
+use serde_json::Value;
+use ffi;
+use python::{Python, PythonObject};
+use err::{PyErr, PyResult};
+use conversion::{ExtractPyObject, ToPyObject};
+use super::{exc, PyObject, PyBool, PyFloat, PyList, PyTuple, PyDict, PyString};
+
+/// Converts a JSON value to the corresponding native Python object:
+/// `null` to `None`, booleans to `bool`, numbers to `int` or `float`,
+/// strings to `str`, arrays to `list` and objects to `dict`.
+impl ToPyObject for Value {
+    type ObjectType = PyObject;
+
+    fn to_py_object(&self, py: Python) -> PyObject {
+        match *self {
+            Value::Null => py.None(),
+            Value::Bool(b) => b.to_py_object(py).into_object(),
+            Value::I64(v) => v.to_py_object(py).into_object(),
+            Value::U64(v) => v.to_py_object(py).into_object(),
+            Value::F64(v) => v.to_py_object(py).into_object(),
+            Value::String(ref s) => s.to_py_object(py).into_object(),
+            Value::Array(ref vec) => vec.to_py_object(py).into_object(),
+            Value::Object(ref map) => map.to_py_object(py).into_object()
+        }
+    }
+}
+
+#[cfg(feature="python27-sys")]
+fn is_int(obj: &PyObject) -> bool {
+    unsafe { ffi::PyInt_Check(obj.as_ptr()) != 0 || ffi::PyLong_Check(obj.as_ptr()) != 0 }
+}
+
+#[cfg(feature="python3-sys")]
+fn is_int(obj: &PyObject) -> bool {
+    unsafe { ffi::PyLong_Check(obj.as_ptr()) != 0 }
+}
+
+fn extract_array<'a, I>(py: Python, items: I) -> PyResult<Value>
+    where I: Iterator<Item=&'a PyObject>
+{
+    let mut vec = Vec::new();
+    for item in items {
+        vec.push(try!(item.extract::<Value>(py)));
+    }
+    Ok(Value::Array(vec))
+}
+
+/// Builds a JSON value from a Python object, recursively converting
+/// `list`, `tuple` and `dict` contents.
+///
+/// Dictionary keys must be strings.
+/// Integers that do not fit into `i64` or `u64` raise `OverflowError`;
+/// unsupported object types raise `TypeError`.
+extract!(obj to Value; py => {
+    if *obj == py.None() {
+        Ok(Value::Null)
+    } else if let Ok(b) = obj.cast_as::<PyBool>(py) {
+        Ok(Value::Bool(b.is_true()))
+    } else if is_int(obj) {
+        match obj.extract::<i64>(py) {
+            Ok(v) => Ok(Value::I64(v)),
+            Err(_) => Ok(Value::U64(try!(obj.extract::<u64>(py))))
+        }
+    } else if obj.cast_as::<PyFloat>(py).is_ok() {
+        Ok(Value::F64(try!(obj.extract::<f64>(py))))
+    } else if let Ok(s) = PyString::extract(py, obj) {
+        Ok(Value::String(s.into_owned()))
+    } else if let Ok(list) = obj.cast_as::<PyList>(py) {
+        let items: Vec<PyObject> = list.iter(py).collect();
+        extract_array(py, items.iter())
+    } else if let Ok(tuple) = obj.cast_as::<PyTuple>(py) {
+        extract_array(py, tuple.as_slice().iter())
+    } else if let Ok(dict) = obj.cast_as::<PyDict>(py) {
+        let mut map = BTreeMap::new();
+        for (key, value) in dict.items(py) {
+            let key = match PyString::extract(py, &key) {
+                Ok(key) => key.into_owned(),
+                Err(_) => return Err(PyErr::new::<exc::TypeError, _>(py,
+                    "JSON object keys must be strings"))
+            };
+            map.insert(key, try!(value.extract::<Value>(py)));
+        }
+        Ok(Value::Object(map))
+    } else {
+        Err(PyErr::new::<exc::TypeError, _>(py, "object is not JSON serializable"))
+    }
+});
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use serde_json::Value;
+    use python::{Python, PythonObject};
+    use conversion::ToPyObject;
+    use objects::PyDict;
+
+    #[test]
+    fn test_round_trip() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut inner = BTreeMap::new();
+        inner.insert("flag".to_owned(), Value::Bool(true));
+        inner.insert("none".to_owned(), Value::Null);
+        inner.insert("big".to_owned(), Value::U64(u64::max_value()));
+        let mut map = BTreeMap::new();
+        map.insert("name".to_owned(), Value::String("test".to_owned()));
+        map.insert("list".to_owned(), Value::Array(vec![Value::I64(-1), Value::F64(2.5)]));
+        map.insert("inner".to_owned(), Value::Object(inner));
+        let value = Value::Object(map);
+
+        let obj = value.to_py_object(py);
+        assert_eq!(value, obj.extract::<Value>(py).unwrap());
+
+        let d = PyDict::new(py);
+        d.set_item(py, "v", obj).unwrap();
+        let ok: bool = py.eval("v['inner']['big'] == 2**64 - 1 and v['list'] == [-1, 2.5]",
+                               None, Some(&d)).unwrap().extract(py).unwrap();
+        assert!(ok);
+    }
+
+    #[test]
+    fn test_extract_from_python() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = py.eval("{'a': [1, (True, None)], 'b': 1.5}", None, None).unwrap();
+        let value = obj.extract::<Value>(py).unwrap();
+        let a = Value::Array(vec![Value::I64(1), Value::Array(vec![Value::Bool(true), Value::Null])]);
+        assert_eq!(Some(&a), value.find("a"));
+        assert_eq!(Some(&Value::F64(1.5)), value.find("b"));
+    }
+}
@@ -19,8 +19,12 @@
 use python::{Python, PythonObject, ToPythonPointer, PyClone};
 use err::{self, PyErr, PyResult};
 use super::object::PyObject;
+use super::{exc, PyTuple};
 use ffi::{self, Py_ssize_t};
 use conversion::{ToPyObject, ExtractPyObject};
+use objectprotocol::ObjectProtocol;
+use std::collections::VecDeque;
+use std::ptr;
 
 /// Represents a Python `list`.
 pub struct PyList(PyObject);
@@ -60,6 +64,22 @@ impl PyList {
         }
     }
 
+    /// Gets a reference to the item at the specified index,
+    /// without incrementing the reference count.
+    ///
+    /// Panics if the index is out of range.
+    ///
+    /// # Safety
+    /// The reference points directly into the list's storage.
+    /// The caller must ensure that the list is not mutated while the reference is alive,
+    /// neither from Rust (e.g. by `set_item`, `insert_item`, `clear` or `pop`)
+    /// nor by Python code that modifies or resizes the list.
+    pub unsafe fn get_borrowed<'a>(&'a self, py: Python, index: usize) -> &'a PyObject {
+        assert!(index < self.len(py));
+        let ptr = self.0.as_ptr() as *mut ffi::PyListObject;
+        PyObject::borrow_from_owned_ptr(&*(*ptr).ob_item.offset(index as isize))
+    }
+
     /// Sets the item at the specified index.
     ///
     /// Panics if the index is out of range.
@@ -76,6 +96,87 @@ impl PyList {
         assert!(r == 0);
     }
 
+    /// Removes all items from the list.
+    /// This is equivalent to the Python statement `del self[:]`.
+    pub fn clear(&self, py: Python) -> PyResult<()> {
+        self.truncate(py, 0)
+    }
+
+    /// Shortens the list to `len` items, removing the rest.
+    /// Has no effect if the list is already shorter than `len`.
+    ///
+    /// This is equivalent to the Python statement `del self[len:]`.
+    pub fn truncate(&self, py: Python, len: usize) -> PyResult<()> {
+        let current = self.len(py);
+        if len >= current {
+            return Ok(());
+        }
+        unsafe {
+            err::error_on_minusone(py, ffi::PyList_SetSlice(self.0.as_ptr(),
+                len as Py_ssize_t, current as Py_ssize_t, ptr::null_mut()))
+        }
+    }
+
+    /// Creates a new tuple containing the items of the list.
+    /// This is equivalent to the Python expression `tuple(self)`.
+    pub fn to_tuple(&self, py: Python) -> PyResult<PyTuple> {
+        unsafe {
+            err::result_cast_from_owned_ptr(py, ffi::PyList_AsTuple(self.0.as_ptr()))
+        }
+    }
+
+    /// Removes and returns the item at `index` (default: the last item).
+    /// Negative indices count from the end of the list.
+    ///
+    /// This is equivalent to the Python expression `self.pop(index)`;
+    /// raises `IndexError` if the list is empty or the index is out of range.
+    pub fn pop(&self, py: Python, index: Option<isize>) -> PyResult<PyObject> {
+        match index {
+            Some(index) => self.0.call_method(py, "pop", (index,), None),
+            None => self.0.call_method(py, "pop", super::NoArgs, None)
+        }
+    }
+
+    /// Returns the index of the first item that is equal to `item`.
+    ///
+    /// This is equivalent to the Python expression `self.index(item)`;
+    /// raises `ValueError` if no such item exists.
+    /// Exceptions raised by `__eq__` are propagated.
+    pub fn index<V>(&self, py: Python, item: V) -> PyResult<usize> where V: ToPyObject {
+        try!(self.0.call_method(py, "index", (item,), None)).extract(py)
+    }
+
+    /// Returns the number of items that are equal to `item`.
+    ///
+    /// This is equivalent to the Python expression `self.count(item)`.
+    /// Exceptions raised by `__eq__` are propagated.
+    pub fn count<V>(&self, py: Python, item: V) -> PyResult<usize> where V: ToPyObject {
+        try!(self.0.call_method(py, "count", (item,), None)).extract(py)
+    }
+
+    /// Sorts the list using a key extracted by the Rust function `f`.
+    ///
+    /// The keys for all items are computed first, then the list is reordered
+    /// according to the sorted keys. The sort is stable.
+    /// Fails with `ValueError` if the list is modified while computing the keys.
+    pub fn sort_by_key<K, F>(&self, py: Python, mut f: F) -> PyResult<()>
+        where F: FnMut(&PyObject) -> PyResult<K>, K: Ord
+    {
+        let items: Vec<PyObject> = self.iter(py).collect();
+        let mut keys = Vec::with_capacity(items.len());
+        for (i, item) in items.iter().enumerate() {
+            keys.push((try!(f(item)), i));
+        }
+        if self.len(py) != items.len() {
+            return Err(PyErr::new::<exc::ValueError, _>(py, "list modified during sort"));
+        }
+        keys.sort_by(|a, b| a.0.cmp(&b.0));
+        for (pos, &(_, i)) in keys.iter().enumerate() {
+            self.set_item(py, pos, items[i].clone_ref(py));
+        }
+        Ok(())
+    }
+
     #[inline]
     pub fn iter<'a, 'p>(&'a self, py: Python<'p>) -> PyListIterator<'a, 'p> {
         PyListIterator { py: py, list: self, index: 0 }
@@ -107,19 +208,38 @@ impl <'a, 'p> Iterator for PyListIterator<'a, 'p> {
     // might change during the iteration.
 }
 
+/// Creates a new list containing the elements of `elements`, converted to Python objects.
+fn list_from_iter<'a, T, I>(py: Python, elements: I) -> PyList
+    where T: 'a + ToPyObject, I: ExactSizeIterator<Item=&'a T>
+{
+    unsafe {
+        let ptr = ffi::PyList_New(elements.len() as Py_ssize_t);
+        let t = err::cast_from_owned_ptr_or_panic(py, ptr);
+        for (i, e) in elements.enumerate() {
+            let obj = e.to_py_object(py).into_object();
+            ffi::PyList_SetItem(ptr, i as Py_ssize_t, obj.steal_ptr());
+        }
+        t
+    }
+}
+
 impl <T> ToPyObject for [T] where T: ToPyObject {
     type ObjectType = PyList;
 
+    #[inline]
     fn to_py_object(&self, py: Python) -> PyList {
-        unsafe {
-            let ptr = ffi::PyList_New(self.len() as Py_ssize_t);
-            let t = err::cast_from_owned_ptr_or_panic(py, ptr);
-            for (i, e) in self.iter().enumerate() {
-                let obj = e.to_py_object(py).into_object();
-                ffi::PyList_SetItem(ptr, i as Py_ssize_t, obj.steal_ptr());
-            }
-            t
-        }
+        list_from_iter(py, self.iter())
+    }
+}
+
+/// Converts a `Vec` to a Python `list`.
+/// Nested vectors (e.g. `Vec<Vec<i64>>`) become nested lists.
+impl <T> ToPyObject for Vec<T> where T: ToPyObject {
+    type ObjectType = PyList;
+
+    #[inline]
+    fn to_py_object(&self, py: Python) -> PyList {
+        self[..].to_py_object(py)
     }
 }
 
@@ -147,11 +267,45 @@ impl <'prepared, T> ExtractPyObject<'prepared> for Vec<T>
      }
 }
 
+/// Converts a `VecDeque` to a Python `list`, in front-to-back order.
+impl <T> ToPyObject for VecDeque<T> where T: ToPyObject {
+    type ObjectType = PyList;
+
+    #[inline]
+    fn to_py_object(&self, py: Python) -> PyList {
+        list_from_iter(py, self.iter())
+    }
+}
+
+/// Extracts a `VecDeque` from any Python iterable, preserving the iteration order.
+impl <'prepared, T> ExtractPyObject<'prepared> for VecDeque<T>
+    where T: ExtractPyObject<'prepared>
+{
+    type Prepared = Vec<T::Prepared>;
+
+    fn prepare_extract(py: Python, obj: &PyObject) -> PyResult<Self::Prepared> {
+        let mut v = Vec::new();
+        for item in try!(obj.iter(py)) {
+            v.push(try!(T::prepare_extract(py, &try!(item))));
+        }
+        Ok(v)
+    }
+
+    fn extract(py: Python, prepared: &'prepared Self::Prepared) -> PyResult<VecDeque<T>> {
+        let mut v = VecDeque::with_capacity(prepared.len());
+        for prepared_elem in prepared {
+            v.push_back(try!(T::extract(py, prepared_elem)));
+        }
+        Ok(v)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use python::{Python, PythonObject};
     use conversion::ToPyObject;
-    use objects::PyList;
+    use objectprotocol::ObjectProtocol;
+    use objects::{PyList, PyDict, exc};
 
     #[test]
     fn test_len() {
@@ -174,6 +328,64 @@ mod test {
         assert_eq!(7, list.get_item(py, 3).extract::<i32>(py).unwrap());
     }
 
+    #[test]
+    fn test_get_borrowed() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let v = vec![2, 3, 5, 7];
+        let list = v.to_py_object(py);
+        for (i, &expected) in v.iter().enumerate() {
+            let item = unsafe { list.get_borrowed(py, i) };
+            let refcnt = item.get_refcnt(py);
+            assert_eq!(expected, item.extract::<i32>(py).unwrap());
+            assert_eq!(refcnt, unsafe { list.get_borrowed(py, i) }.get_refcnt(py));
+        }
+    }
+
+    #[test]
+    fn test_sort_by_key() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let list = py.eval("[type('Item', (object,), {'value': v, 'name': n})() \
+                             for v, n in [(3, 'c'), (1, 'a'), (2, 'b'), (1, 'a2')]]", None, None)
+                     .unwrap().cast_into::<PyList>(py).unwrap();
+        list.sort_by_key(py, |item| try!(item.getattr(py, "value")).extract::<i32>(py)).unwrap();
+        let names: Vec<String> = list.iter(py)
+            .map(|item| item.getattr(py, "name").unwrap().extract(py).unwrap())
+            .collect();
+        assert_eq!(vec!["a", "a2", "b", "c"], names);
+    }
+
+    #[test]
+    fn test_to_tuple() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let list = vec![1, 2, 3].to_py_object(py);
+        let tuple = list.to_tuple(py).unwrap();
+        assert_eq!(3, tuple.len(py));
+        for i in 0 .. 3 {
+            assert!(tuple.get_item(py, i) == list.get_item(py, i));
+        }
+        let list2 = tuple.to_list(py).unwrap();
+        assert_eq!(vec![1, 2, 3], list2.into_object().extract::<Vec<i32>>(py).unwrap());
+    }
+
+    #[test]
+    fn test_pop() {
+        use objects::exc;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let list = vec![1, 2, 3, 4].to_py_object(py);
+        assert_eq!(4, list.pop(py, None).unwrap().extract::<i32>(py).unwrap());
+        assert_eq!(1, list.pop(py, Some(0)).unwrap().extract::<i32>(py).unwrap());
+        assert_eq!(2, list.pop(py, Some(-2)).unwrap().extract::<i32>(py).unwrap());
+        assert_eq!(1, list.len(py));
+        assert!(list.pop(py, Some(5)).is_err());
+        assert_eq!(3, list.pop(py, None).unwrap().extract::<i32>(py).unwrap());
+        let err = list.pop(py, None).err().unwrap();
+        assert!(err.matches(py, py.get_type::<exc::IndexError>().as_object()));
+    }
+
     #[test]
     fn test_set_item() {
         let gil = Python::acquire_gil();
@@ -224,4 +436,91 @@ mod test {
         let v2 = list.into_object().extract::<Vec<i32>>(py).unwrap();
         assert_eq!(v, v2);
     }
+
+    #[test]
+    fn test_vec_deque() {
+        use std::collections::VecDeque;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut v = VecDeque::new();
+        v.push_back(2i64);
+        v.push_back(3);
+        v.push_front(1);
+        let list = v.to_py_object(py);
+        assert_eq!(1, list.get_item(py, 0).extract::<i64>(py).unwrap());
+        assert_eq!(3, list.get_item(py, 2).extract::<i64>(py).unwrap());
+        let v2 = list.into_object().extract::<VecDeque<i64>>(py).unwrap();
+        assert_eq!(v, v2);
+        assert_eq!(Some(&1), v2.front());
+        assert_eq!(Some(&3), v2.back());
+
+        let range = py.eval("iter((4, 5, 6))", None, None).unwrap();
+        let v3 = range.extract::<VecDeque<i64>>(py).unwrap();
+        assert_eq!(vec![4, 5, 6], v3.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_nested_vec() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let v: Vec<Vec<i64>> = vec![vec![1, 2], vec![], vec![3]];
+        let list = v.to_py_object(py);
+        assert_eq!(3, list.len(py));
+        assert_eq!(0, list.get_item(py, 1).cast_into::<PyList>(py).unwrap().len(py));
+        let s: String = list.as_object().str(py).unwrap().into_object().extract(py).unwrap();
+        assert_eq!("[[1, 2], [], [3]]", s);
+        assert_eq!(v, list.into_object().extract::<Vec<Vec<i64>>>(py).unwrap());
+    }
+
+    #[test]
+    fn test_nested_vec_3d() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let v: Vec<Vec<Vec<i64>>> = vec![vec![vec![1, 2], vec![3]], vec![], vec![vec![]]];
+        let list = v.to_py_object(py);
+        let s: String = list.as_object().str(py).unwrap().into_object().extract(py).unwrap();
+        assert_eq!("[[[1, 2], [3]], [], [[]]]", s);
+        assert_eq!(v, list.into_object().extract::<Vec<Vec<Vec<i64>>>>(py).unwrap());
+    }
+
+    #[test]
+    fn test_clear_truncate() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let list = vec![1, 2, 3, 4, 5].to_py_object(py);
+        list.truncate(py, 7).unwrap();
+        assert_eq!(5, list.len(py));
+        list.truncate(py, 2).unwrap();
+        assert_eq!(vec![1, 2], list.as_object().extract::<Vec<i32>>(py).unwrap());
+        let list = vec![1, 2, 3, 4, 5].to_py_object(py);
+        list.clear(py).unwrap();
+        assert_eq!(0, list.len(py));
+        list.clear(py).unwrap();
+        assert_eq!(0, list.len(py));
+    }
+
+    #[test]
+    fn test_index_count() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let list = vec![3, 1, 4, 1, 5].to_py_object(py);
+        assert_eq!(2, list.index(py, 4).unwrap());
+        assert_eq!(1, list.index(py, 1).unwrap());
+        let err = list.index(py, 9).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::ValueError>().as_object()));
+        assert_eq!(2, list.count(py, 1).unwrap());
+        assert_eq!(0, list.count(py, 9).unwrap());
+    }
+
+    #[test]
+    fn test_index_count_eq_error() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let d = PyDict::new(py);
+        py.run("class Bad(object):\n    def __eq__(self, other):\n        raise RuntimeError()\n", None, Some(&d)).unwrap();
+        let list = py.eval("[Bad()]", None, Some(&d)).unwrap().cast_into::<PyList>(py).unwrap();
+        let err = list.index(py, 1).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::RuntimeError>().as_object()));
+        assert!(list.count(py, 1).is_err());
+    }
 }
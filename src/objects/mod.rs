@@ -20,7 +20,8 @@ pub use self::object::PyObject;
 pub use self::typeobject::PyType;
 pub use self::module::PyModule;
 
-pub use self::string::{PyBytes, PyUnicode};
+pub use self::string::{PyBytes, PyUnicode, ByteSlice, ByteVec};
+pub use self::bytearray::PyByteArray;
 // Directly exporting self::string::PyString causes a compiler error?
 #[cfg(feature="python27-sys")]
 pub use self::string::PyBytes as PyString;
@@ -29,8 +30,8 @@ pub use self::string::PyUnicode as PyString;
 
 pub use self::iterator::PyIterator;
 pub use self::boolobject::PyBool;
-pub use self::tuple::{PyTuple, NoArgs};
-pub use self::dict::PyDict;
+pub use self::tuple::{PyTuple, PyTuplePool, PyTupleArray, NoArgs, ResultTuple};
+pub use self::dict::{PyDict, PyDictBuilder};
 pub use self::list::PyList;
 #[cfg(feature="python27-sys")]
 pub use self::num::PyInt;
@@ -38,6 +39,8 @@ pub use self::num::PyInt;
 pub use self::num::PyLong as PyInt;
 pub use self::num::{PyLong, PyFloat};
 pub use self::sequence::PySequence;
+pub use self::duration::DurationSeconds;
+pub use self::array::{ArrayElement, ArraySlice, ArrayVec};
 
 /// Identity conversion: allows using existing `PyObject` instances where
 /// `T: ToPyObject` is expected.
@@ -151,6 +154,24 @@ macro_rules! extract(
                 $body
             }
         }
+    };
+    // Variant that replaces TypeErrors with "expected $expected, got <type name>"
+    ($obj:ident to $t:ty, expected $expected:expr; $py:ident => $body: block) => {
+        impl <'prepared> ::conversion::ExtractPyObject<'prepared>
+            for $t
+        {
+            type Prepared = PyObject;
+
+            #[inline]
+            fn prepare_extract(py: Python, obj: &PyObject) -> PyResult<Self::Prepared> {
+                Ok(::python::PyClone::clone_ref(obj, py))
+            }
+
+            fn extract($py: Python, $obj: &'prepared PyObject) -> PyResult<Self> {
+                let result: PyResult<Self> = (|| $body)();
+                result.map_err(|e| ::conversion::type_error_expected($py, e, $obj, $expected))
+            }
+        }
     }
 );
 
@@ -158,6 +179,7 @@ mod object;
 mod typeobject;
 mod module;
 mod string;
+mod bytearray;
 mod dict;
 mod iterator;
 mod boolobject;
@@ -165,6 +187,18 @@ mod tuple;
 mod list;
 mod num;
 mod sequence;
+mod fraction;
+mod range;
+mod duration;
+mod array;
+#[cfg(feature="serde-json")]
+mod json;
+#[cfg(feature="uuid")]
+mod uuid;
+#[cfg(feature="chrono")]
+mod chrono;
+#[cfg(feature="half")]
+mod half;
 pub mod exc;
 
 #[cfg(feature="python27-sys")]
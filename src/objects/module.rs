@@ -116,3 +116,23 @@ impl PyModule {
 }
 
 
+
+#[cfg(test)]
+mod test {
+    use python::{Python, PythonObject};
+    use objectprotocol::ObjectProtocol;
+    use objects::PyModule;
+
+    #[test]
+    fn test_module_dict() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let m = PyModule::new(py, "my_module").unwrap();
+        assert_eq!("my_module", m.name(py).unwrap());
+        let dict = m.dict(py);
+        assert_eq!("my_module", dict.get_item(py, "__name__").unwrap().extract::<String>(py).unwrap());
+        dict.set_item(py, "answer", 42).unwrap();
+        assert_eq!(42, m.get(py, "answer").unwrap().extract::<i32>(py).unwrap());
+        assert!(m.as_object().getattr(py, "answer").is_ok());
+    }
+}
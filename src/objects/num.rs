@@ -18,6 +18,8 @@
 
 extern crate num;
 
+use std::ptr;
+use self::num::complex::Complex;
 use libc::{c_long, c_double};
 use python::{Python, PythonObject, PyClone};
 use err::{self, PyResult, PyErr};
@@ -25,6 +27,8 @@ use super::object::PyObject;
 use super::exc;
 use ffi;
 use conversion::{ToPyObject, ExtractPyObject};
+use objectprotocol::ObjectProtocol;
+use super::NoArgs;
 
 /// Represents a Python `int` object.
 ///
@@ -78,6 +82,41 @@ impl PyInt {
 }
 
 
+impl PyLong {
+    /// Gets the number of bits necessary to represent the absolute value of this integer,
+    /// excluding the sign and leading zeros.
+    /// This is equivalent to the Python expression `self.bit_length()`.
+    pub fn bit_length(&self, py: Python) -> PyResult<usize> {
+        try!(self.0.call_method(py, "bit_length", NoArgs, None)).extract(py)
+    }
+
+    /// Converts this integer to `f64`, returning `None` if the value
+    /// cannot be represented exactly as a double
+    /// (either because precision would be lost, or because it is out of range).
+    pub fn to_f64_exact(&self, py: Python) -> PyResult<Option<f64>> {
+        let v = unsafe { ffi::PyLong_AsDouble(self.0.as_ptr()) };
+        if v == -1.0 && PyErr::occurred(py) {
+            let e = PyErr::fetch(py);
+            if e.matches(py, py.get_type::<exc::OverflowError>().as_object()) {
+                return Ok(None);
+            }
+            return Err(e);
+        }
+        let back = unsafe { try!(err::result_from_owned_ptr(py, ffi::PyLong_FromDouble(v))) };
+        let r = unsafe { ffi::PyObject_RichCompareBool(self.0.as_ptr(), back.as_ptr(), ffi::Py_EQ) };
+        if r < 0 {
+            return Err(PyErr::fetch(py));
+        }
+        Ok(if r == 1 { Some(v) } else { None })
+    }
+
+    /// Returns true if this integer is negative.
+    #[inline]
+    pub fn is_negative(&self, _py: Python) -> PyResult<bool> {
+        Ok(unsafe { ffi::_PyLong_Sign(self.0.as_ptr()) } < 0)
+    }
+}
+
 impl PyFloat {
     /// Creates a new Python `float` object.
     pub fn new(py: Python, val: c_double) -> PyFloat {
@@ -90,6 +129,26 @@ impl PyFloat {
     pub fn value(&self, _py: Python) -> c_double {
         unsafe { ffi::PyFloat_AsDouble(self.0.as_ptr()) }
     }
+
+    /// Returns the shortest decimal representation that round-trips to the same float,
+    /// as produced by the Python expression `repr(self)` (e.g. `"0.1"`, `"5e-324"`, `"1e+300"`).
+    ///
+    /// Unlike Rust's formatting, this always uses Python's notation,
+    /// including for special values (`"inf"`, `"nan"`).
+    pub fn to_shortest_string(&self, py: Python) -> PyResult<String> {
+        Ok(try!(try!(self.0.repr(py)).to_string(py)).into_owned())
+    }
+
+    /// Computes the hash of this float, as done by the Python expression `hash(self)`.
+    ///
+    /// Integer-valued floats hash equal to the corresponding `int`,
+    /// so the result can be used to keep Rust-side hashed collections consistent with Python.
+    /// Hashing a `float` cannot fail, so this method does not return a `PyResult`.
+    pub fn py_hash(&self, _py: Python) -> isize {
+        let h = unsafe { ffi::PyObject_Hash(self.0.as_ptr()) };
+        debug_assert!(h != -1);
+        h as isize
+    }
 }
 
 macro_rules! int_fits_c_long(
@@ -118,11 +177,8 @@ macro_rules! int_fits_c_long(
             }
         }
 
-        extract!(obj to $rust_type; py => {
-            let val = unsafe { ffi::PyLong_AsLong(obj.as_ptr()) };
-            if val == -1 && PyErr::occurred(py) {
-                return Err(PyErr::fetch(py));
-            }
+        extract!(obj to $rust_type, expected "int"; py => {
+            let val = try!(extract_c_long(py, obj));
             match num::traits::cast::<c_long, $rust_type>(val) {
                 Some(v) => Ok(v),
                 None => Err(overflow_error(py))
@@ -143,7 +199,7 @@ macro_rules! int_fits_larger_int(
             }
         }
 
-        extract!(obj to $rust_type; py => {
+        extract!(obj to $rust_type, expected "int"; py => {
             let val = try!(obj.extract::<$larger_type>(py));
             match num::traits::cast::<$larger_type, $rust_type>(val) {
                 Some(v) => Ok(v),
@@ -154,6 +210,41 @@ macro_rules! int_fits_larger_int(
 );
 
 
+fn extract_c_long(py: Python, obj: &PyObject) -> PyResult<c_long> {
+    unsafe {
+        let val = ffi::PyLong_AsLong(obj.as_ptr());
+        if val == -1 && PyErr::occurred(py) {
+            let index = try!(index_fallback(py, obj.as_ptr(), PyErr::fetch(py)));
+            return err_if_invalid_value(py, -1, ffi::PyLong_AsLong(index.as_ptr()));
+        }
+        Ok(val)
+    }
+}
+
+/// Slow path for integer extraction: converts an arbitrary object using `__int__`,
+/// or `__index__` if that fails.
+/// Kept out of line so that the fast path for actual `int` objects stays small.
+#[inline(never)]
+unsafe fn number_to_long(py: Python, ptr: *mut ffi::PyObject) -> PyResult<PyObject> {
+    match err::result_from_owned_ptr(py, ffi::PyNumber_Long(ptr)) {
+        Ok(num) => Ok(num),
+        Err(e) => index_fallback(py, ptr, e)
+    }
+}
+
+/// Called after converting `ptr` to an integer failed with `err`.
+/// If the failure was a `TypeError` and the object supports the `__index__` protocol
+/// (e.g. numpy integers), returns the result of `operator.index(obj)`;
+/// otherwise returns `err`.
+#[inline(never)]
+unsafe fn index_fallback(py: Python, ptr: *mut ffi::PyObject, err: PyErr) -> PyResult<PyObject> {
+    if ffi::PyLong_Check(ptr) != 0 || ffi::PyIndex_Check(ptr) == 0
+        || !err.matches(py, py.get_type::<exc::TypeError>().as_object()) {
+        return Err(err);
+    }
+    err::result_from_owned_ptr(py, ffi::PyNumber_Index(ptr))
+}
+
 fn err_if_invalid_value<'p, T: PartialEq>
     (py: Python, invalid_value: T, actual_value: T) -> PyResult<T>
 {
@@ -203,33 +294,34 @@ macro_rules! int_convert_u64_or_i64 (
             #[cfg(feature="python27-sys")]
             fn extract(py: Python, obj: &'prepared PyObject) -> PyResult<$rust_type> {
                 let ptr = obj.as_ptr();
-
-                unsafe {
-                    if ffi::PyLong_Check(ptr) != 0 {
-                        err_if_invalid_value(py, !0, $pylong_as_ull_or_ull(ptr))
-                    } else if ffi::PyInt_Check(ptr) != 0 {
-                        match num::traits::cast::<c_long, $rust_type>(ffi::PyInt_AS_LONG(ptr)) {
+                let result: PyResult<$rust_type> = (|| unsafe {
+                    // Small integers are usually `int`, so check for that first.
+                    if ffi::PyInt_Check(ptr) != 0 {
+                        return match num::traits::cast::<c_long, $rust_type>(ffi::PyInt_AS_LONG(ptr)) {
                             Some(v) => Ok(v),
                             None => Err(overflow_error(py))
-                        }
-                    } else {
-                        let num = try!(err::result_from_owned_ptr(py, ffi::PyNumber_Long(ptr)));
-                        err_if_invalid_value(py, !0, $pylong_as_ull_or_ull(num.as_ptr()))
+                        };
                     }
-                }
+                    if ffi::PyLong_Check(ptr) != 0 {
+                        return err_if_invalid_value(py, !0, $pylong_as_ull_or_ull(ptr));
+                    }
+                    let num = try!(number_to_long(py, ptr));
+                    err_if_invalid_value(py, !0, $pylong_as_ull_or_ull(num.as_ptr()))
+                })();
+                result.map_err(|e| ::conversion::type_error_expected(py, e, obj, "int"))
             }
 
             #[cfg(feature="python3-sys")]
             fn extract(py: Python, obj: &'prepared PyObject) -> PyResult<$rust_type> {
                 let ptr = obj.as_ptr();
-                unsafe {
+                let result: PyResult<$rust_type> = (|| unsafe {
                     if ffi::PyLong_Check(ptr) != 0 {
-                        err_if_invalid_value(py, !0, $pylong_as_ull_or_ull(ptr))
-                    } else {
-                        let num = try!(err::result_from_owned_ptr(py, ffi::PyNumber_Long(ptr)));
-                        err_if_invalid_value(py, !0, $pylong_as_ull_or_ull(num.as_ptr()))
+                        return err_if_invalid_value(py, !0, $pylong_as_ull_or_ull(ptr));
                     }
-                }
+                    let num = try!(number_to_long(py, ptr));
+                    err_if_invalid_value(py, !0, $pylong_as_ull_or_ull(num.as_ptr()))
+                })();
+                result.map_err(|e| ::conversion::type_error_expected(py, e, obj, "int"))
             }
         }
     )
@@ -273,7 +365,7 @@ impl ToPyObject for f64 {
     }
 }
 
-extract!(obj to f64; py => {
+extract!(obj to f64, expected "float"; py => {
     let v = unsafe { ffi::PyFloat_AsDouble(obj.as_ptr()) };
     if v == -1.0 && PyErr::occurred(py) {
         Err(PyErr::fetch(py))
@@ -282,6 +374,41 @@ extract!(obj to f64; py => {
     }
 });
 
+/// Converts a `Complex<f64>` to a Python `complex`.
+impl ToPyObject for Complex<f64> {
+    type ObjectType = PyObject;
+
+    fn to_py_object(&self, py: Python) -> PyObject {
+        unsafe {
+            err::from_owned_ptr_or_panic(py, ffi::PyComplex_FromDoubles(self.re, self.im))
+        }
+    }
+}
+
+/// Extracts a `Complex<f64>` from a Python `complex`.
+/// Other objects, like `float`, `int` or objects implementing `__complex__`,
+/// are converted using `complex(obj)` first. Strings are not accepted.
+extract!(obj to Complex<f64>, expected "complex"; py => {
+    let obj = unsafe {
+        if ffi::PyComplex_Check(obj.as_ptr()) != 0 {
+            obj.clone_ref(py)
+        } else if ffi::PyUnicode_Check(obj.as_ptr()) != 0 || ffi::PyBytes_Check(obj.as_ptr()) != 0 {
+            // complex() would parse the string
+            return Err(PyErr::new_lazy_init(py.get_type::<exc::TypeError>(), None));
+        } else {
+            let complex_type = &mut ffi::PyComplex_Type as *mut ffi::PyTypeObject as *mut ffi::PyObject;
+            try!(err::result_from_owned_ptr(py,
+                ffi::PyObject_CallFunctionObjArgs(complex_type, obj.as_ptr(), ptr::null_mut::<ffi::PyObject>())))
+        }
+    };
+    let real = unsafe { ffi::PyComplex_RealAsDouble(obj.as_ptr()) };
+    if real == -1.0 && PyErr::occurred(py) {
+        return Err(PyErr::fetch(py));
+    }
+    let imag = unsafe { ffi::PyComplex_ImagAsDouble(obj.as_ptr()) };
+    Ok(Complex::new(real, imag))
+});
+
 fn overflow_error(py: Python) -> PyErr {
     PyErr::new_lazy_init(py.get_type::<exc::OverflowError>(), None)
 }
@@ -294,7 +421,7 @@ impl ToPyObject for f32 {
     }
 }
 
-extract!(obj to f32; py => {
+extract!(obj to f32, expected "float"; py => {
     Ok(try!(obj.extract::<f64>(py)) as f32)
 });
 
@@ -303,6 +430,8 @@ mod test {
     use std;
     use python::{Python, PythonObject};
     use conversion::ToPyObject;
+    use objectprotocol::ObjectProtocol;
+    use objects::{PyFloat, PyDict};
 
     macro_rules! num_to_py_object_and_back (
         ($func_name:ident, $t1:ty, $t2:ty) => (
@@ -378,4 +507,143 @@ mod test {
         assert_eq!(v, obj.extract::<u64>(py).unwrap());
         assert!(obj.extract::<i64>(py).is_err());
     }
+
+    #[test]
+    fn test_extract_many_u64() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let list = py.eval("list(range(10000))", None, None).unwrap();
+        let v = list.extract::<Vec<u64>>(py).unwrap();
+        assert_eq!(10000 * 9999 / 2, v.iter().fold(0, |a, b| a + b));
+        // non-int objects still go through __int__
+        let obj = py.eval("2.0", None, None).unwrap();
+        assert_eq!(2, obj.extract::<u64>(py).unwrap());
+    }
+
+    #[test]
+    fn test_extract_index() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let d = PyDict::new(py);
+        py.run("class Index(object):\n    def __index__(self):\n        return 5\n",
+               None, Some(&d)).unwrap();
+        let obj = py.eval("Index()", None, Some(&d)).unwrap();
+        assert_eq!(5, obj.extract::<i64>(py).unwrap());
+        assert_eq!(5, obj.extract::<u64>(py).unwrap());
+        assert_eq!(5, obj.extract::<i32>(py).unwrap());
+        assert_eq!(5, obj.extract::<usize>(py).unwrap());
+        let obj = py.eval("object()", None, None).unwrap();
+        assert!(obj.extract::<i64>(py).is_err());
+    }
+
+    #[test]
+    fn test_complex() {
+        use super::num::complex::Complex;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let c = Complex::new(1.5, -2.0);
+        let obj = c.to_py_object(py);
+        assert_eq!(c, obj.extract::<Complex<f64>>(py).unwrap());
+        let obj = py.eval("complex(3, 4)", None, None).unwrap();
+        assert_eq!(Complex::new(3.0, 4.0), obj.extract::<Complex<f64>>(py).unwrap());
+        let obj = 2.5.to_py_object(py).into_object();
+        assert_eq!(Complex::new(2.5, 0.0), obj.extract::<Complex<f64>>(py).unwrap());
+        let obj = 7.to_py_object(py).into_object();
+        assert_eq!(Complex::new(7.0, 0.0), obj.extract::<Complex<f64>>(py).unwrap());
+        let d = PyDict::new(py);
+        py.run("class C(object):\n    def __complex__(self): return 1-1j\n", None, Some(&d)).unwrap();
+        let obj = py.eval("C()", None, Some(&d)).unwrap();
+        assert_eq!(Complex::new(1.0, -1.0), obj.extract::<Complex<f64>>(py).unwrap());
+        let obj = "1+2j".to_py_object(py).into_object();
+        assert!(obj.extract::<Complex<f64>>(py).is_err());
+    }
+
+    #[test]
+    fn test_extract_type_error_message() {
+        use objectprotocol::ObjectProtocol;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let list = py.eval("[1, 2]", None, None).unwrap();
+        let mut err = list.extract::<i64>(py).err().unwrap();
+        let msg: String = err.instance(py).str(py).unwrap().into_object().extract(py).unwrap();
+        assert_eq!("expected int, got list", msg);
+        let mut err = list.extract::<i32>(py).err().unwrap();
+        let msg: String = err.instance(py).str(py).unwrap().into_object().extract(py).unwrap();
+        assert_eq!("expected int, got list", msg);
+        let mut err = list.extract::<f64>(py).err().unwrap();
+        let msg: String = err.instance(py).str(py).unwrap().into_object().extract(py).unwrap();
+        assert_eq!("expected float, got list", msg);
+        // errors other than TypeError are kept
+        let big = py.eval("10 ** 30", None, None).unwrap();
+        let err = big.extract::<i32>(py).err().unwrap();
+        assert!(err.matches(py, py.get_type::<super::exc::OverflowError>().as_object()));
+    }
+
+    #[test]
+    fn test_float_to_shortest_string() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        assert_eq!("0.1", PyFloat::new(py, 0.1).to_shortest_string(py).unwrap());
+        assert_eq!("5e-324", PyFloat::new(py, 5e-324).to_shortest_string(py).unwrap());
+        assert_eq!("1.7976931348623157e+308",
+                   PyFloat::new(py, std::f64::MAX).to_shortest_string(py).unwrap());
+        assert_eq!("1e+300", PyFloat::new(py, 1e300).to_shortest_string(py).unwrap());
+        assert_eq!("2.0", PyFloat::new(py, 2.0).to_shortest_string(py).unwrap());
+    }
+
+    #[test]
+    fn test_float_hash() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let f = PyFloat::new(py, 2.0);
+        assert_eq!(f.py_hash(py), 2i32.to_py_object(py).into_object().hash(py).unwrap() as isize);
+        let f = PyFloat::new(py, -1.0);
+        assert_eq!(f.py_hash(py), -2);
+
+        let nan = PyFloat::new(py, ::std::f64::NAN);
+        let d = PyDict::new(py);
+        d.set_item(py, "nan", &nan).unwrap();
+        let expected: isize = py.eval("hash(nan)", None, Some(&d)).unwrap().extract(py).unwrap();
+        assert_eq!(expected, nan.py_hash(py));
+    }
+
+    #[test]
+    fn test_bit_length() {
+        use objects::PyLong;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let v = py.eval("1 << 100", None, None).unwrap().cast_into::<PyLong>(py).unwrap();
+        assert_eq!(101, v.bit_length(py).unwrap());
+        assert!(!v.is_negative(py).unwrap());
+    }
+
+    #[test]
+    fn test_is_negative() {
+        use objects::PyLong;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let v = py.eval("-(1 << 100)", None, None).unwrap().cast_into::<PyLong>(py).unwrap();
+        assert!(v.is_negative(py).unwrap());
+        assert_eq!(101, v.bit_length(py).unwrap());
+        let zero = py.eval("0", None, None).unwrap();
+        // on Python 2.7, small integer literals are `int`, not `long`
+        if let Ok(zero) = zero.cast_into::<PyLong>(py) {
+            assert!(!zero.is_negative(py).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_to_f64_exact() {
+        use objects::PyLong;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        // on Python 2.7, small integer literals are `int`, not `long`
+        let long_type = if cfg!(feature="python27-sys") { "long" } else { "int" };
+        let long = |code: &str| py.eval(&format!("{}({})", long_type, code), None, None).unwrap()
+                                  .cast_into::<PyLong>(py).unwrap();
+        assert_eq!(Some(9007199254740992.0), long("2**53").to_f64_exact(py).unwrap());
+        assert_eq!(None, long("2**53 + 1").to_f64_exact(py).unwrap());
+        assert_eq!(Some(42.0), long("42").to_f64_exact(py).unwrap());
+        assert_eq!(None, long("2**2000").to_f64_exact(py).unwrap());
+    }
 }
@@ -17,10 +17,37 @@
 // DEALINGS IN THE SOFTWARE.
 
 use std::mem;
+use std::any::TypeId;
+use std::ffi::CStr;
+use std::sync::Arc;
+use libc;
+use abort_on_panic::PanicGuard;
 use ffi;
 use python::{Python, PythonObject, PythonObjectWithCheckedDowncast, PythonObjectWithTypeObject, PythonObjectDowncastError};
-use objects::PyType;
-use err::PyResult;
+use objects::{PyType, exc};
+use err::{PyErr, PyResult};
+
+static mut ENUM_MODULE: *mut ffi::PyObject = 0 as *mut ffi::PyObject;
+
+/// Name of the attribute used by `PyObject::attach_rust()`.
+const RUST_DATA_ATTR: &'static str = "__rust_data__";
+/// Capsule name used by `PyObject::attach_rust()`, to distinguish our capsules from others.
+const RUST_DATA_CAPSULE_NAME: &'static [u8] = b"cpython.rust_data\0";
+
+/// Contents of the capsule created by `PyObject::attach_rust()`.
+/// `type_id` must be the first field so that it can be read without knowing `T`.
+#[repr(C)]
+struct RustData<T> {
+    type_id: TypeId,
+    value: Arc<T>
+}
+
+unsafe extern "C" fn rust_data_capsule_destructor<T>(capsule: *mut ffi::PyObject) where T: 'static {
+    let _guard = PanicGuard::with_message("Rust panic in attached Rust data destructor");
+    let name = RUST_DATA_CAPSULE_NAME.as_ptr() as *const libc::c_char;
+    let data = ffi::PyCapsule_GetPointer(capsule, name) as *mut RustData<T>;
+    drop(Box::from_raw(data));
+}
 
 /// Represents a reference to a Python object.
 ///
@@ -51,17 +78,40 @@ pub struct PyObject {
 }
 
 /// Dropping a `PyObject` decrements the reference count on the object by 1.
+///
+/// In debug builds, dropping a `PyObject` whose reference count already is zero
+/// (e.g. because extension code released the same reference twice)
+/// panics with the object's type name instead of corrupting the heap.
 impl Drop for PyObject {
     #[inline]
     fn drop(&mut self) {
         // TODO: remove `if` when #[unsafe_no_drop_flag] disappears
         if self.ptr as usize != mem::POST_DROP_USIZE {
             let _gil_guard = Python::acquire_gil();
-            unsafe { ffi::Py_DECREF(self.ptr); }
+            unsafe {
+                if cfg!(debug_assertions) && ffi::Py_REFCNT(self.ptr) <= 0 {
+                    refcnt_underflow(self.ptr);
+                }
+                ffi::Py_DECREF(self.ptr);
+            }
         }
     }
 }
 
+#[cold]
+#[inline(never)]
+unsafe fn refcnt_underflow(ptr: *mut ffi::PyObject) -> ! {
+    // The object is already freed, so the type pointer is only a best-effort guess.
+    let ty = ffi::Py_TYPE(ptr);
+    let name = if ty.is_null() || (*ty).tp_name.is_null() {
+        "<unknown>".into()
+    } else {
+        CStr::from_ptr((*ty).tp_name).to_string_lossy()
+    };
+    panic!("dropping PyObject of type '{}' whose reference count already is zero \
+            (was the reference released twice?)", name);
+}
+
 pyobject_to_pyobject!(PyObject);
 
 impl PythonObject for PyObject {
@@ -154,6 +204,18 @@ impl PyObject {
         self.ptr
     }
 
+    /// Gets the underlying FFI pointer, like `as_ptr()`.
+    ///
+    /// In debug builds, this asserts that the object is still alive (reference count > 0),
+    /// catching some use-after-free bugs early.
+    /// In release builds, this is identical to `as_ptr()`.
+    #[inline]
+    pub fn as_ptr_checked(&self, _py: Python) -> *mut ffi::PyObject {
+        debug_assert!(unsafe { ffi::Py_REFCNT(self.ptr) } > 0,
+            "PyObject used after its reference count dropped to zero");
+        self.ptr
+    }
+
     /// Gets the underlying FFI pointer.
     /// Consumes `self` without calling `Py_DECREF()`, thus returning an owned pointer.
     #[inline]
@@ -235,6 +297,19 @@ impl PyObject {
         PythonObjectWithCheckedDowncast::downcast_borrow_from(py, self)
     }
 
+    /// Borrows the PyObject as a concrete Python object type,
+    /// returning `None` if the object is not of the expected type.
+    /// Like `cast_as()`, this does not change the reference count.
+    ///
+    /// This is convenient for dispatching over several possible types:
+    /// `if let Some(list) = obj.downcast_ref::<PyList>(py) { ... }`
+    #[inline]
+    pub fn downcast_ref<'s, T>(&'s self, py: Python) -> Option<&'s T>
+        where T: PythonObjectWithCheckedDowncast
+    {
+        self.cast_as(py).ok()
+    }
+
     /// Extracts some type from the Python object.
     /// This is a wrapper function around `FromPyObject::from_py_object()`.
     #[inline]
@@ -244,6 +319,121 @@ impl PyObject {
         let prepared = try!(<T as ::conversion::ExtractPyObject>::prepare_extract(py, self));
         <T as ::conversion::ExtractPyObject>::extract(py, &prepared)
     }
+
+    /// Extracts some type from the Python object, mapping Python `None` to `Ok(None)`.
+    ///
+    /// Any other value is extracted as `T`; extraction errors
+    /// (including type mismatches) are returned as `Err`.
+    #[inline]
+    pub fn extract_or_none<T>(&self, py: Python) -> PyResult<Option<T>>
+        where T: for<'prep> ::conversion::ExtractPyObject<'prep>
+    {
+        if unsafe { self.ptr == ffi::Py_None() } {
+            Ok(None)
+        } else {
+            self.extract(py).map(Some)
+        }
+    }
+
+    /// Converts any Python number to an `f64`.
+    ///
+    /// This is equivalent to the Python expression `float(self)` restricted to
+    /// numbers: `int`, `bool`, `float` and objects implementing `__float__` are accepted.
+    /// Raises `TypeError` for non-numeric objects (including strings).
+    #[inline]
+    pub fn as_f64(&self, py: Python) -> PyResult<f64> {
+        let v = unsafe { ffi::PyFloat_AsDouble(self.ptr) };
+        if v == -1.0 && PyErr::occurred(py) {
+            Err(PyErr::fetch(py))
+        } else {
+            Ok(v)
+        }
+    }
+
+    /// Returns the `.value` of an `enum.Enum` member.
+    ///
+    /// Raises `TypeError` if the object is not an `enum.Enum` member
+    /// (including on Python versions without the `enum` module).
+    pub fn enum_value(&self, py: Python) -> PyResult<PyObject> {
+        use objectprotocol::ObjectProtocol;
+        let enum_module = try!(unsafe { ::python::import_cached(py, &mut ENUM_MODULE, "enum") });
+        let enum_class = try!(enum_module.get(py, "Enum"));
+        let is_enum = unsafe { ffi::PyObject_IsInstance(self.ptr, enum_class.as_ptr()) };
+        if is_enum == -1 {
+            return Err(PyErr::fetch(py));
+        } else if is_enum == 0 {
+            return Err(PyErr::new::<exc::TypeError, _>(py,
+                format!("expected enum.Enum member, got {}", self.get_type().name(py))));
+        }
+        self.getattr(py, "value")
+    }
+
+    /// Attaches a Rust value to this Python object, replacing any value
+    /// attached previously.
+    ///
+    /// The value is stored in a capsule in the `__rust_data__` attribute,
+    /// so this fails with `AttributeError` if the object does not accept new attributes.
+    /// It is dropped when the attribute is removed or replaced.
+    /// Use `get_rust()` to retrieve the value.
+    pub fn attach_rust<T>(&self, py: Python, value: T) -> PyResult<()> where T: 'static + Send + Sync {
+        use objectprotocol::ObjectProtocol;
+        let data = Box::new(RustData { type_id: TypeId::of::<T>(), value: Arc::new(value) });
+        let capsule = unsafe {
+            let data = Box::into_raw(data);
+            let name = RUST_DATA_CAPSULE_NAME.as_ptr() as *const libc::c_char;
+            let capsule = ffi::PyCapsule_New(data as *mut libc::c_void, name, Some(rust_data_capsule_destructor::<T>));
+            if capsule.is_null() {
+                drop(Box::from_raw(data));
+                return Err(PyErr::fetch(py));
+            }
+            PyObject::from_owned_ptr(py, capsule)
+        };
+        self.setattr(py, RUST_DATA_ATTR, capsule)
+    }
+
+    /// Retrieves the Rust value attached to this object by `attach_rust()`.
+    ///
+    /// Raises `TypeError` if no value is attached or if the attached value
+    /// is not of type `T`.
+    pub fn get_rust<T>(&self, py: Python) -> PyResult<Arc<T>> where T: 'static + Send + Sync {
+        use objectprotocol::ObjectProtocol;
+        let name = RUST_DATA_CAPSULE_NAME.as_ptr() as *const libc::c_char;
+        let capsule = match try!(self.getattr_opt(py, RUST_DATA_ATTR)) {
+            Some(capsule) => capsule,
+            None => return Err(PyErr::new::<exc::TypeError, _>(py, "object has no attached Rust data"))
+        };
+        unsafe {
+            if ffi::PyCapsule_IsValid(capsule.as_ptr(), name) == 0 {
+                return Err(PyErr::new::<exc::TypeError, _>(py,
+                    format!("{} is not attached Rust data", RUST_DATA_ATTR)));
+            }
+            let data = ffi::PyCapsule_GetPointer(capsule.as_ptr(), name);
+            if *(data as *const TypeId) != TypeId::of::<T>() {
+                return Err(PyErr::new::<exc::TypeError, _>(py, "attached Rust data has a different type"));
+            }
+            Ok((*(data as *const RustData<T>)).value.clone())
+        }
+    }
+
+    /// Computes `repr(self)`, truncated to at most `max_len` code points.
+    ///
+    /// If the representation is longer than `max_len`, the first `max_len`
+    /// code points are kept and `"..."` is appended.
+    /// Useful for logging objects that may be arbitrarily large.
+    pub fn repr_limited(&self, py: Python, max_len: usize) -> PyResult<String> {
+        use objectprotocol::ObjectProtocol;
+        let repr = try!(self.repr(py));
+        let repr = try!(repr.to_string(py));
+        match repr.char_indices().nth(max_len) {
+            Some((idx, _)) => {
+                let mut s = String::with_capacity(idx + 3);
+                s.push_str(&repr[..idx]);
+                s.push_str("...");
+                Ok(s)
+            }
+            None => Ok(repr.into_owned())
+        }
+    }
 }
 
 /// PyObject implements the `==` operator using reference equality:
@@ -267,3 +457,128 @@ fn test_sizeof() {
     assert_eq!(mem::size_of::<PyType>(), mem::size_of::<*mut ffi::PyTypeObject>());
 }
 
+#[test]
+fn test_clone_ref() {
+    use python::PyClone;
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let obj = py.eval("object()", None, None).unwrap();
+    let refcnt = obj.get_refcnt(py);
+    let obj2 = obj.clone_ref(py);
+    assert!(obj == obj2);
+    assert_eq!(refcnt + 1, obj.get_refcnt(py));
+    drop(obj2);
+    assert_eq!(refcnt, obj.get_refcnt(py));
+}
+
+#[test]
+fn test_extract_or_none() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    assert_eq!(None, py.None().extract_or_none::<i64>(py).unwrap());
+    let obj = py.eval("5", None, None).unwrap();
+    assert_eq!(Some(5), obj.extract_or_none::<i64>(py).unwrap());
+    let obj = py.eval("'five'", None, None).unwrap();
+    assert!(obj.extract_or_none::<i64>(py).is_err());
+}
+
+#[test]
+fn test_as_f64() {
+    use objects::{PyDict, exc};
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    assert_eq!(5.0, py.eval("5", None, None).unwrap().as_f64(py).unwrap());
+    assert_eq!(1.0, py.eval("True", None, None).unwrap().as_f64(py).unwrap());
+    let d = PyDict::new(py);
+    py.run("class F(object):\n    def __float__(self):\n        return 2.5\nf = F()", None, Some(&d)).unwrap();
+    let f = d.get_item(py, "f").unwrap();
+    assert_eq!(2.5, f.as_f64(py).unwrap());
+    let err = py.eval("'1.5'", None, None).unwrap().as_f64(py).unwrap_err();
+    assert!(err.matches(py, py.get_type::<exc::TypeError>().as_object()));
+}
+
+#[test]
+#[cfg(feature="python3-sys")]
+fn test_enum_value() {
+    use objects::PyDict;
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let d = PyDict::new(py);
+    py.run("import enum\nclass Color(enum.Enum):\n    RED = 'r'\n", None, Some(&d)).unwrap();
+    let red = py.eval("Color.RED", None, Some(&d)).unwrap();
+    assert_eq!("r", red.enum_value(py).unwrap().extract::<String>(py).unwrap());
+    let err = py.eval("'r'", None, None).unwrap().enum_value(py).unwrap_err();
+    assert!(err.matches(py, py.get_type::<exc::TypeError>().as_object()));
+}
+
+#[test]
+fn test_attach_rust() {
+    use objects::PyDict;
+    #[derive(Debug, PartialEq)]
+    struct Plugin { name: &'static str }
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let d = PyDict::new(py);
+    py.run("class C(object): pass\nobj = C()", None, Some(&d)).unwrap();
+    let obj = d.get_item(py, "obj").unwrap();
+    assert!(obj.get_rust::<Plugin>(py).is_err());
+    obj.attach_rust(py, Plugin { name: "p" }).unwrap();
+    assert_eq!("p", obj.get_rust::<Plugin>(py).unwrap().name);
+    let err = obj.get_rust::<i32>(py).unwrap_err();
+    assert!(err.matches(py, py.get_type::<exc::TypeError>().as_object()));
+    // objects without __dict__ can't hold attached data
+    let obj = py.eval("object()", None, None).unwrap();
+    assert!(obj.attach_rust(py, 1).is_err());
+}
+
+#[test]
+fn test_repr_limited() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let obj = py.eval("list(range(1000))", None, None).unwrap();
+    assert_eq!("[0, 1, 2, ...", obj.repr_limited(py, 10).unwrap());
+    let obj = py.eval("[1, 2]", None, None).unwrap();
+    assert_eq!("[1, 2]", obj.repr_limited(py, 6).unwrap());
+    assert_eq!("[1, 2...", obj.repr_limited(py, 5).unwrap());
+}
+
+#[test]
+fn test_downcast_ref() {
+    use objects::{PyList, PyDict};
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let obj = py.eval("[1, 2]", None, None).unwrap();
+    let refcnt = obj.get_refcnt(py);
+    let list = obj.downcast_ref::<PyList>(py).unwrap();
+    assert_eq!(2, list.len(py));
+    assert_eq!(refcnt, obj.get_refcnt(py));
+    assert!(obj.downcast_ref::<PyDict>(py).is_none());
+    let obj = py.eval("{}", None, None).unwrap();
+    assert!(obj.downcast_ref::<PyList>(py).is_none());
+    assert!(obj.downcast_ref::<PyDict>(py).is_some());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn test_drop_checks_refcnt_silently() {
+    use python::PyClone;
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let obj = py.eval("object()", None, None).unwrap();
+    let refcnt = obj.get_refcnt(py);
+    for _ in 0..3 {
+        drop(obj.clone_ref(py));
+    }
+    assert_eq!(refcnt, obj.get_refcnt(py));
+    drop(obj);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn test_as_ptr_checked() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let obj = py.eval("object()", None, None).unwrap();
+    assert_eq!(obj.as_ptr(), obj.as_ptr_checked(py));
+}
@@ -0,0 +1,96 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Conversions between `std::ops::Range` and Python `range` objects
+//! (`xrange` in Python 2).
+
+use std::ops::Range;
+use ffi;
+use python::{Python, PythonObject, ToPythonPointer};
+use err::{PyErr, PyResult};
+use conversion::{ExtractPyObject, ToPyObject};
+use objectprotocol::ObjectProtocol;
+use super::{exc, PyObject, PyTuple, PyType};
+
+/// Converts `start..end` to the Python `range(start, end)`.
+///
+/// Panics if the range cannot be created
+/// (in Python 2, the bounds must fit into a C `long`).
+impl ToPyObject for Range<i64> {
+    type ObjectType = PyObject;
+
+    fn to_py_object(&self, py: Python) -> PyObject {
+        let range_type = unsafe { PyType::from_type_ptr(py, &mut ffi::PyRange_Type) };
+        range_type.call(py, (self.start, self.end), None)
+            .expect("failed to create range object")
+    }
+}
+
+/// Extracts `start..stop` from a Python `range(start, stop)`.
+///
+/// Raises `TypeError` if the object is not a `range`,
+/// and `ValueError` if the step of the range is not 1.
+extract!(obj to Range<i64>; py => {
+    if unsafe { ffi::PyRange_Check(obj.as_ptr()) } == 0 {
+        return Err(PyErr::new::<exc::TypeError, _>(py,
+            format!("expected range, got {}", obj.get_type().name(py))));
+    }
+    // Python 2 `xrange` objects do not expose start/stop/step as attributes,
+    // but both versions support pickling as `(range, (start, stop, step))`.
+    let reduced = try!(try!(obj.reduce(py)).cast_into::<PyTuple>(py));
+    let args = try!(reduced.get_item(py, 1).cast_into::<PyTuple>(py));
+    let (start, stop, step): (i64, i64, i64) = try!(args.into_object().extract(py));
+    if step != 1 {
+        return Err(PyErr::new::<exc::ValueError, _>(py,
+            format!("cannot convert range with step {} to a Rust range", step)));
+    }
+    Ok(start .. stop)
+});
+
+#[cfg(test)]
+mod test {
+    use python::{Python, PythonObject};
+    use conversion::ToPyObject;
+    use objects::PyDict;
+    use objectprotocol::ObjectProtocol;
+
+    #[test]
+    fn test_round_trip() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = (0i64 .. 10).to_py_object(py);
+        assert_eq!(10, obj.len(py).unwrap());
+        assert_eq!(0i64 .. 10, obj.extract(py).unwrap());
+        let d = PyDict::new(py);
+        d.set_item(py, "r", &obj).unwrap();
+        assert_eq!(45, py.eval("sum(r)", None, Some(&d)).unwrap().extract::<i64>(py).unwrap());
+    }
+
+    #[test]
+    fn test_extract() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let builtin = if cfg!(feature="python27-sys") { "xrange" } else { "range" };
+        let obj = py.eval(&format!("{}(-5, 5)", builtin), None, None).unwrap();
+        assert_eq!(-5i64 .. 5, obj.extract(py).unwrap());
+        let obj = py.eval(&format!("{}(0, 10, 2)", builtin), None, None).unwrap();
+        assert!(obj.extract::<::std::ops::Range<i64>>(py).is_err());
+        let obj = py.eval("[0, 1, 2]", None, None).unwrap();
+        assert!(obj.extract::<::std::ops::Range<i64>>(py).is_err());
+    }
+}
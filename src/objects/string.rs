@@ -20,15 +20,22 @@ use std;
 use std::str;
 use std::ascii::AsciiExt;
 use std::borrow::Cow;
+use std::ffi::CString;
+use std::net::{IpAddr, Ipv6Addr, SocketAddr, SocketAddrV6};
 use libc::c_char;
 use ffi;
 use python::{Python, PythonObject, PyClone, ToPythonPointer};
-use super::{exc, PyObject};
+use super::{exc, PyObject, PyList};
 use err::{self, PyResult, PyErr};
 use conversion::{ExtractPyObject, ToPyObject};
+use objectprotocol::ObjectProtocol;
 
 /// Represents a Python byte string.
 /// Corresponds to `str` in Python 2, and `bytes` in Python 3.
+///
+/// Note that `&[u8]` and `Vec<u8>` convert to a Python `list` of integers,
+/// like slices and vectors of any other element type.
+/// Use the `ByteSlice` and `ByteVec` wrappers to convert binary data.
 pub struct PyBytes(PyObject);
 
 /// Represents a Python unicode string.
@@ -65,6 +72,23 @@ impl PyBytes {
             std::slice::from_raw_parts(buffer, length)
         }
     }
+
+    /// Decodes the byte string into a unicode string using the codec registered for `encoding`.
+    /// `errors` is the error handling scheme, e.g. `"strict"` or `"replace"`.
+    ///
+    /// This is equivalent to the Python expression `self.decode(encoding, errors)`.
+    /// Raises `LookupError` for unknown encodings, and `UnicodeDecodeError`
+    /// if the data is invalid (when using the `"strict"` error handler).
+    pub fn decode(&self, py: Python, encoding: &str, errors: &str) -> PyResult<PyUnicode> {
+        let data = self.as_slice(py);
+        let encoding = CString::new(encoding).unwrap();
+        let errors = CString::new(errors).unwrap();
+        unsafe {
+            err::result_cast_from_owned_ptr(py,
+                ffi::PyUnicode_Decode(data.as_ptr() as *const c_char, data.len() as ffi::Py_ssize_t,
+                                      encoding.as_ptr(), errors.as_ptr()))
+        }
+    }
 }
 
 impl PyUnicode {
@@ -78,6 +102,21 @@ impl PyUnicode {
         }
     }
 
+    /// Encodes the unicode string into a byte string using the codec registered for `encoding`.
+    /// `errors` is the error handling scheme, e.g. `"strict"` or `"replace"`.
+    ///
+    /// This is equivalent to the Python expression `self.encode(encoding, errors)`.
+    /// Raises `LookupError` for unknown encodings, and `UnicodeEncodeError`
+    /// if a character cannot be encoded (when using the `"strict"` error handler).
+    pub fn encode(&self, py: Python, encoding: &str, errors: &str) -> PyResult<PyBytes> {
+        let encoding = CString::new(encoding).unwrap();
+        let errors = CString::new(errors).unwrap();
+        unsafe {
+            err::result_cast_from_owned_ptr(py,
+                ffi::PyUnicode_AsEncodedString(self.as_ptr(), encoding.as_ptr(), errors.as_ptr()))
+        }
+    }
+
     /* Note: 'as_slice removed temporarily, we need to reconsider
     // whether we really should expose the platform-dependent Py_UNICODE to user code.
     #[cfg(feature="python27-sys")]
@@ -230,6 +269,179 @@ impl PyString {
         }
     }
 
+    /// Concatenates two strings.
+    /// This is equivalent to the Python expression `self + other`.
+    #[cfg(feature="python3-sys")]
+    pub fn concat(&self, py: Python, other: &PyString) -> PyResult<PyString> {
+        unsafe {
+            err::result_cast_from_owned_ptr(py,
+                ffi::PyUnicode_Concat(self.as_ptr(), other.as_ptr()))
+        }
+    }
+
+    /// Concatenates two strings.
+    /// This is equivalent to the Python expression `self + other`.
+    #[cfg(feature="python27-sys")]
+    pub fn concat(&self, py: Python, other: &PyString) -> PyResult<PyString> {
+        unsafe {
+            err::result_cast_from_owned_ptr(py,
+                ffi::PySequence_Concat(self.as_ptr(), other.as_ptr()))
+        }
+    }
+
+    /// Compares the string with a Rust string, without extracting the Python string.
+    /// The comparison is exact: strings are equal only if they contain the same code points.
+    pub fn eq_str(&self, py: Python, s: &str) -> PyResult<bool> {
+        s.with_borrowed_ptr(py, |s| unsafe {
+            match ffi::PyObject_RichCompareBool(self.as_ptr(), s, ffi::Py_EQ) {
+                -1 => Err(PyErr::fetch(py)),
+                r => Ok(r != 0)
+            }
+        })
+    }
+
+    /// Splits the string into a list of strings, using `sep` as the delimiter.
+    /// If `sep` is `None`, runs of consecutive whitespace are treated as a single separator.
+    /// At most `maxsplit` splits are done; a negative value means no limit.
+    ///
+    /// This is equivalent to the Python expression `self.split(sep, maxsplit)`.
+    pub fn split(&self, py: Python, sep: Option<&str>, maxsplit: isize) -> PyResult<PyList> {
+        let sep = match sep {
+            Some(sep) => sep.to_py_object(py).into_object(),
+            None => py.None()
+        };
+        let result = try!(self.as_object().call_method(py, "split", (sep, maxsplit), None));
+        Ok(try!(result.cast_into(py)))
+    }
+
+    /// Splits the string at line boundaries.
+    /// Line breaks are included in the resulting strings if `keepends` is true.
+    ///
+    /// This is equivalent to the Python expression `self.splitlines(keepends)`.
+    pub fn splitlines(&self, py: Python, keepends: bool) -> PyResult<PyList> {
+        let result = try!(self.as_object().call_method(py, "splitlines", (keepends,), None));
+        Ok(try!(result.cast_into(py)))
+    }
+
+    /// Returns true if the string starts with `prefix`.
+    ///
+    /// This is equivalent to the Python expression `self.startswith(prefix)`.
+    pub fn startswith(&self, py: Python, prefix: &str) -> PyResult<bool> {
+        try!(self.as_object().call_method(py, "startswith", (prefix,), None)).is_true(py)
+    }
+
+    /// Returns true if the string ends with `suffix`.
+    ///
+    /// This is equivalent to the Python expression `self.endswith(suffix)`.
+    pub fn endswith(&self, py: Python, suffix: &str) -> PyResult<bool> {
+        try!(self.as_object().call_method(py, "endswith", (suffix,), None)).is_true(py)
+    }
+
+    /// Returns a copy of the string with leading and trailing characters removed.
+    /// `chars` specifies the set of characters to remove;
+    /// if it is `None`, whitespace is removed.
+    ///
+    /// This is equivalent to the Python expression `self.strip(chars)`.
+    pub fn strip(&self, py: Python, chars: Option<&str>) -> PyResult<PyString> {
+        let chars = match chars {
+            Some(chars) => chars.to_py_object(py).into_object(),
+            None => py.None()
+        };
+        let result = try!(self.as_object().call_method(py, "strip", (chars,), None));
+        Ok(try!(result.cast_into(py)))
+    }
+
+    /// Returns the index of the first occurrence of `sub` in the string,
+    /// or `None` if `sub` is not found.
+    ///
+    /// This is equivalent to the Python expression `self.find(sub)`.
+    /// In Python 3, the index counts code points, not UTF-8 bytes;
+    /// in Python 2.7 (where `PyString` is a byte string), it counts bytes.
+    pub fn find(&self, py: Python, sub: &str) -> PyResult<Option<usize>> {
+        let index: isize = try!(try!(self.as_object().call_method(py, "find", (sub,), None)).extract(py));
+        Ok(if index < 0 { None } else { Some(index as usize) })
+    }
+
+    /// Returns the index of the last occurrence of `sub` in the string,
+    /// or `None` if `sub` is not found.
+    ///
+    /// This is equivalent to the Python expression `self.rfind(sub)`;
+    /// see `find()` for how the index is counted.
+    pub fn rfind(&self, py: Python, sub: &str) -> PyResult<Option<usize>> {
+        let index: isize = try!(try!(self.as_object().call_method(py, "rfind", (sub,), None)).extract(py));
+        Ok(if index < 0 { None } else { Some(index as usize) })
+    }
+
+    /// Returns the number of non-overlapping occurrences of `sub` in the string.
+    ///
+    /// This is equivalent to the Python expression `self.count(sub)`.
+    pub fn count(&self, py: Python, sub: &str) -> PyResult<usize> {
+        try!(self.as_object().call_method(py, "count", (sub,), None)).extract(py)
+    }
+
+    /// Returns a copy of the string with occurrences of `old` replaced by `new`.
+    /// If `count` is given, only the first `count` occurrences are replaced.
+    ///
+    /// This is equivalent to the Python expression `self.replace(old, new, count)`.
+    pub fn replace(&self, py: Python, old: &str, new: &str, count: Option<isize>) -> PyResult<PyString> {
+        let count = count.unwrap_or(-1);
+        let result = try!(self.as_object().call_method(py, "replace", (old, new, count), None));
+        Ok(try!(result.cast_into(py)))
+    }
+
+    /// Concatenates the strings in `iterable`, using `self` as the separator.
+    ///
+    /// This is equivalent to the Python expression `self.join(iterable)`.
+    /// Raises `TypeError` if any element of `iterable` is not a string.
+    pub fn join(&self, py: Python, iterable: &PyObject) -> PyResult<PyString> {
+        let result = try!(self.as_object().call_method(py, "join", (iterable,), None));
+        Ok(try!(result.cast_into(py)))
+    }
+
+    /// Formats the string using the values in `mapping` for named fields.
+    ///
+    /// This is equivalent to the Python expression `self.format_map(mapping)`;
+    /// unlike `self.format(**mapping)`, the mapping is used directly without being copied.
+    /// Raises `KeyError` for fields missing from the mapping.
+    #[cfg(feature="python3-sys")]
+    pub fn format_map(&self, py: Python, mapping: &PyObject) -> PyResult<PyString> {
+        let result = try!(self.as_object().call_method(py, "format_map", (mapping,), None));
+        Ok(try!(result.cast_into(py)))
+    }
+
+    /// Returns a copy of the string converted to lowercase.
+    /// This is equivalent to the Python expression `self.lower()`.
+    pub fn lower(&self, py: Python) -> PyResult<PyString> {
+        let result = try!(self.as_object().call_method(py, "lower", super::NoArgs, None));
+        Ok(try!(result.cast_into(py)))
+    }
+
+    /// Returns a copy of the string converted to uppercase.
+    /// This is equivalent to the Python expression `self.upper()`.
+    pub fn upper(&self, py: Python) -> PyResult<PyString> {
+        let result = try!(self.as_object().call_method(py, "upper", super::NoArgs, None));
+        Ok(try!(result.cast_into(py)))
+    }
+
+    /// Returns a titlecased copy of the string, where each word starts
+    /// with an uppercase character and the remaining characters are lowercase.
+    /// This is equivalent to the Python expression `self.title()`.
+    pub fn title(&self, py: Python) -> PyResult<PyString> {
+        let result = try!(self.as_object().call_method(py, "title", super::NoArgs, None));
+        Ok(try!(result.cast_into(py)))
+    }
+
+    /// Returns a casefolded copy of the string, for use in caseless comparisons.
+    ///
+    /// Casefolding is more aggressive than `lower()`; for example,
+    /// the German `"\u{df}"` is casefolded to `"ss"`.
+    /// This is equivalent to the Python expression `self.casefold()`.
+    #[cfg(feature="python3-sys")]
+    pub fn casefold(&self, py: Python) -> PyResult<PyString> {
+        let result = try!(self.as_object().call_method(py, "casefold", super::NoArgs, None));
+        Ok(try!(result.cast_into(py)))
+    }
+
     // In Python 2.7, PyBytes serves as PyString, so it should offer the
     // same to_string and to_string_lossy functions as PyUnicode:
 
@@ -259,6 +471,45 @@ impl PyString {
     }
 }
 
+/// Wrapper that converts a byte slice to a Python byte string
+/// instead of a list of integers.
+pub struct ByteSlice<'a>(pub &'a [u8]);
+
+/// Wrapper for converting a `Vec<u8>` to and from a Python byte string.
+///
+/// Extraction fails with `TypeError` if the object is not a byte string.
+/// In Python 2.7, `unicode` objects are also accepted and encoded with the default encoding.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ByteVec(pub Vec<u8>);
+
+impl <'a> ToPyObject for ByteSlice<'a> {
+    type ObjectType = PyBytes;
+
+    #[inline]
+    fn to_py_object(&self, py: Python) -> PyBytes {
+        PyBytes::new(py, self.0)
+    }
+}
+
+impl ToPyObject for ByteVec {
+    type ObjectType = PyBytes;
+
+    #[inline]
+    fn to_py_object(&self, py: Python) -> PyBytes {
+        PyBytes::new(py, &self.0)
+    }
+}
+
+extract!(obj to ByteVec; py => {
+    let mut buffer: *mut c_char = std::ptr::null_mut();
+    let mut length: ffi::Py_ssize_t = 0;
+    unsafe {
+        try!(err::error_on_minusone(py,
+            ffi::PyBytes_AsStringAndSize(obj.as_ptr(), &mut buffer, &mut length)));
+        Ok(ByteVec(std::slice::from_raw_parts(buffer as *const u8, length as usize).to_vec()))
+    }
+});
+
 // When converting strings to/from Python, we need to copy the string data.
 // This means we can implement ToPyObject for str, but FromPyObject only for (Cow)String.
 
@@ -351,10 +602,75 @@ impl <'prepared> ExtractPyObject<'prepared> for &'prepared str {
     }
 }
 
+/// Converts rust `IpAddr` to its canonical string representation.
+impl ToPyObject for IpAddr {
+    type ObjectType = <str as ToPyObject>::ObjectType;
+
+    #[inline]
+    fn to_py_object(&self, py: Python) -> Self::ObjectType {
+        <str as ToPyObject>::to_py_object(&self.to_string(), py)
+    }
+}
+
+/// Converts rust `SocketAddr` to its canonical string representation,
+/// e.g. `"127.0.0.1:80"` or `"[fe80::1%2]:80"`.
+impl ToPyObject for SocketAddr {
+    type ObjectType = <str as ToPyObject>::ObjectType;
+
+    fn to_py_object(&self, py: Python) -> Self::ObjectType {
+        let s = match *self {
+            SocketAddr::V6(ref a) if a.scope_id() != 0 =>
+                format!("[{}%{}]:{}", a.ip(), a.scope_id(), a.port()),
+            _ => self.to_string()
+        };
+        <str as ToPyObject>::to_py_object(&s, py)
+    }
+}
+
+/// Allows extracting an `IpAddr` from a Python string.
+/// Raises `ValueError` if the string is not a valid IPv4 or IPv6 address.
+extract!(obj to IpAddr; py => {
+    let s = try!(PyString::extract(py, obj));
+    s.parse().map_err(|_| PyErr::new::<exc::ValueError, _>(py,
+        format!("invalid IP address: '{}'", s)))
+});
+
+/// Allows extracting a `SocketAddr` from a Python string.
+/// Raises `ValueError` if the string is not a valid socket address.
+extract!(obj to SocketAddr; py => {
+    let s = try!(PyString::extract(py, obj));
+    match parse_socket_addr(&s) {
+        Some(addr) => Ok(addr),
+        None => Err(PyErr::new::<exc::ValueError, _>(py,
+            format!("invalid socket address: '{}'", s)))
+    }
+});
+
+/// Parses a socket address, accepting an IPv6 scope id of the form `[ip%scope]:port`.
+fn parse_socket_addr(s: &str) -> Option<SocketAddr> {
+    if let Ok(addr) = s.parse() {
+        return Some(addr);
+    }
+    if !s.starts_with('[') {
+        return None;
+    }
+    let end = match s.find(']') { Some(end) => end, None => return None };
+    let (host, rest) = (&s[1..end], &s[end+1..]);
+    let pct = match host.find('%') { Some(pct) => pct, None => return None };
+    if !rest.starts_with(':') {
+        return None;
+    }
+    match (host[..pct].parse::<Ipv6Addr>(), host[pct+1..].parse::<u32>(), rest[1..].parse::<u16>()) {
+        (Ok(ip), Ok(scope_id), Ok(port)) => Some(SocketAddr::V6(SocketAddrV6::new(ip, port, 0, scope_id))),
+        _ => None
+    }
+}
+
 #[cfg(test)]
 mod test {
     use python::{Python, PythonObject};
     use conversion::{ToPyObject, ExtractPyObject};
+    use objects::{exc, PyString};
 
     #[test]
     fn test_non_bmp() {
@@ -374,5 +690,258 @@ mod test {
         let prepared = <&str>::prepare_extract(py, &py_string).unwrap();
         assert_eq!(s, <&str>::extract(py, &prepared).unwrap());
     }
-}
 
+    #[test]
+    fn test_ip_addr() {
+        use std::net::IpAddr;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        for s in &["127.0.0.1", "2001:db8::1"] {
+            let addr: IpAddr = s.parse().unwrap();
+            let py_string = addr.to_py_object(py).into_object();
+            assert_eq!(*s, py_string.extract::<String>(py).unwrap());
+            assert_eq!(addr, py_string.extract::<IpAddr>(py).unwrap());
+        }
+        let py_string = "not an address".to_py_object(py).into_object();
+        assert!(py_string.extract::<IpAddr>(py).is_err());
+    }
+
+    #[test]
+    fn test_socket_addr() {
+        use std::net::SocketAddr;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        for s in &["127.0.0.1:8080", "[2001:db8::1]:443", "[fe80::1%3]:80"] {
+            let py_string = s.to_py_object(py).into_object();
+            let addr = py_string.extract::<SocketAddr>(py).unwrap();
+            let py_string = addr.to_py_object(py).into_object();
+            assert_eq!(*s, py_string.extract::<String>(py).unwrap());
+        }
+        let py_string = "127.0.0.1".to_py_object(py).into_object();
+        assert!(py_string.extract::<SocketAddr>(py).is_err());
+    }
+
+    #[test]
+    fn test_split() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let s = PyString::new(py, "a,b,,c".as_ref());
+        let parts = s.split(py, Some(","), -1).unwrap();
+        let parts: Vec<String> = parts.as_object().extract(py).unwrap();
+        assert_eq!(vec!["a", "b", "", "c"], parts);
+        let parts = s.split(py, Some(","), 1).unwrap();
+        let parts: Vec<String> = parts.as_object().extract(py).unwrap();
+        assert_eq!(vec!["a", "b,,c"], parts);
+    }
+
+    #[test]
+    fn test_split_whitespace() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let s = PyString::new(py, "  hello   world \t ".as_ref());
+        let parts = s.split(py, None, -1).unwrap();
+        let parts: Vec<String> = parts.as_object().extract(py).unwrap();
+        assert_eq!(vec!["hello", "world"], parts);
+    }
+
+    #[test]
+    fn test_splitlines() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let s = PyString::new(py, "one\ntwo\r\nthree".as_ref());
+        let lines: Vec<String> = s.splitlines(py, false).unwrap().as_object().extract(py).unwrap();
+        assert_eq!(vec!["one", "two", "three"], lines);
+        let lines: Vec<String> = s.splitlines(py, true).unwrap().as_object().extract(py).unwrap();
+        assert_eq!(vec!["one\n", "two\r\n", "three"], lines);
+    }
+
+    #[test]
+    fn test_concat() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let a = PyString::new(py, "Hello, ".as_ref());
+        let b = PyString::new(py, "World".as_ref());
+        let c = a.concat(py, &b).unwrap();
+        assert_eq!("Hello, World", c.into_object().extract::<String>(py).unwrap());
+    }
+
+    #[test]
+    fn test_eq_str() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let s = PyString::new(py, "abc".as_ref());
+        assert!(s.eq_str(py, "abc").unwrap());
+        assert!(!s.eq_str(py, "abd").unwrap());
+        assert!(!s.eq_str(py, "ab").unwrap());
+    }
+
+    #[test]
+    fn test_encode() {
+        use objects::PyUnicode;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let s = PyUnicode::new(py, "caf\u{e9}");
+        let bytes = s.encode(py, "utf-8", "strict").unwrap();
+        assert_eq!(b"caf\xc3\xa9", bytes.as_slice(py));
+        let bytes = s.encode(py, "latin-1", "strict").unwrap();
+        assert_eq!(b"caf\xe9", bytes.as_slice(py));
+        let err = s.encode(py, "ascii", "strict").err().unwrap();
+        assert!(err.matches(py, py.get_type::<exc::UnicodeEncodeError>().as_object()));
+        let bytes = s.encode(py, "ascii", "replace").unwrap();
+        assert_eq!(b"caf?", bytes.as_slice(py));
+        let err = s.encode(py, "no-such-codec", "strict").err().unwrap();
+        assert!(err.matches(py, py.get_type::<exc::LookupError>().as_object()));
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        use objects::PyBytes;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let data = b"a\0b\xff\0";
+        let obj = PyBytes::new(py, data).into_object();
+        assert_eq!(5, obj.len(py).unwrap());
+        let bytes = obj.cast_into::<PyBytes>(py).unwrap();
+        assert_eq!(&data[..], bytes.as_slice(py));
+    }
+
+    #[test]
+    fn test_byte_wrappers_round_trip() {
+        use super::{ByteSlice, ByteVec};
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let data = b"a\0b\xff\0";
+        let obj = ByteSlice(data).to_py_object(py).into_object();
+        assert_eq!(5, obj.len(py).unwrap());
+        assert_eq!(ByteVec(data.to_vec()), obj.extract::<ByteVec>(py).unwrap());
+        let obj = ByteVec(data.to_vec()).to_py_object(py).into_object();
+        assert_eq!(&data[..], &obj.extract::<ByteVec>(py).unwrap().0[..]);
+        let err = 5.to_py_object(py).into_object().extract::<ByteVec>(py).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::TypeError>().as_object()));
+    }
+
+    #[test]
+    fn test_decode() {
+        use objects::PyBytes;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let bytes = PyBytes::new(py, b"caf\xe9");
+        let s = bytes.decode(py, "latin-1", "strict").unwrap();
+        assert_eq!("caf\u{e9}", s.to_string(py).unwrap());
+        let err = bytes.decode(py, "utf-8", "strict").err().unwrap();
+        assert!(err.matches(py, py.get_type::<exc::UnicodeDecodeError>().as_object()));
+    }
+
+    #[test]
+    fn test_startswith_endswith() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let s = PyString::new(py, "hello world".as_ref());
+        assert!(s.startswith(py, "hello").unwrap());
+        assert!(!s.startswith(py, "world").unwrap());
+        assert!(s.endswith(py, "world").unwrap());
+        assert!(!s.endswith(py, "hello").unwrap());
+        assert!(s.startswith(py, "").unwrap());
+    }
+
+    #[test]
+    fn test_strip() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let s = PyString::new(py, " \t hello \n".as_ref());
+        assert_eq!("hello", s.strip(py, None).unwrap().to_string(py).unwrap());
+        let s = PyString::new(py, "xxhelloyx".as_ref());
+        assert_eq!("hello", s.strip(py, Some("xy")).unwrap().to_string(py).unwrap());
+        assert_eq!("xxhelloyx", s.strip(py, Some("")).unwrap().to_string(py).unwrap());
+    }
+
+    #[test]
+    fn test_count() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let s = PyString::new(py, "aaaa".as_ref());
+        assert_eq!(2, s.count(py, "aa").unwrap());
+        assert_eq!(4, s.count(py, "a").unwrap());
+        assert_eq!(0, s.count(py, "b").unwrap());
+    }
+
+    #[test]
+    fn test_replace() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let s = PyString::new(py, "a-b-c-d".as_ref());
+        assert_eq!("a+b+c+d", s.replace(py, "-", "+", None).unwrap().to_string(py).unwrap());
+        assert_eq!("a+b+c-d", s.replace(py, "-", "+", Some(2)).unwrap().to_string(py).unwrap());
+        assert_eq!("a-b-c-d", s.replace(py, "-", "+", Some(0)).unwrap().to_string(py).unwrap());
+        let s = PyString::new(py, "aaaa".as_ref());
+        assert_eq!("bb", s.replace(py, "aa", "b", None).unwrap().to_string(py).unwrap());
+    }
+
+    #[test]
+    fn test_join() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let sep = PyString::new(py, ", ".as_ref());
+        let items = vec!["a", "b", "c"].to_py_object(py).into_object();
+        assert_eq!("a, b, c", sep.join(py, &items).unwrap().to_string(py).unwrap());
+        let items = vec![1, 2].to_py_object(py).into_object();
+        let err = sep.join(py, &items).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::TypeError>().as_object()));
+    }
+
+    #[test]
+    #[cfg(feature="python3-sys")]
+    fn test_format_map() {
+        use objects::PyDict;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let template = PyString::new(py, "Hello, {name}!");
+        let d = PyDict::new(py);
+        d.set_item(py, "name", "x").unwrap();
+        assert_eq!("Hello, x!", template.format_map(py, d.as_object()).unwrap().to_string(py).unwrap());
+        let empty = PyDict::new(py);
+        let err = template.format_map(py, empty.as_object()).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::KeyError>().as_object()));
+    }
+
+    #[test]
+    fn test_case_conversion() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let s = PyString::new(py, "HELLO wOrld".as_ref());
+        assert_eq!("hello world", s.lower(py).unwrap().to_string(py).unwrap());
+        assert_eq!("HELLO WORLD", s.upper(py).unwrap().to_string(py).unwrap());
+        assert_eq!("Hello World", s.title(py).unwrap().to_string(py).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature="python3-sys")]
+    fn test_casefold() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let s = PyString::new(py, "Stra\u{df}e");
+        assert_eq!("strasse", s.casefold(py).unwrap().to_string(py).unwrap());
+        assert_eq!("stra\u{df}e", s.lower(py).unwrap().to_string(py).unwrap());
+    }
+
+    #[test]
+    fn test_find() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let s = PyString::new(py, "abcabc".as_ref());
+        assert_eq!(Some(1), s.find(py, "bc").unwrap());
+        assert_eq!(Some(4), s.rfind(py, "bc").unwrap());
+        assert_eq!(None, s.find(py, "x").unwrap());
+        assert_eq!(None, s.rfind(py, "x").unwrap());
+    }
+
+    #[test]
+    #[cfg(feature="python3-sys")]
+    fn test_find_counts_code_points() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let s = PyString::new(py, "h\u{e9}llo w\u{f6}rld \u{1F30F}!");
+        assert_eq!(Some(6), s.find(py, "w\u{f6}rld").unwrap());
+        assert_eq!(Some(13), s.rfind(py, "!").unwrap());
+    }
+}
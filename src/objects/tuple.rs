@@ -16,13 +16,13 @@
 // OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-use python::{Python, PythonObject, ToPythonPointer};
+use python::{Python, PythonObject, ToPythonPointer, PyDrop, PyClone};
 use err::{self, PyErr, PyResult};
 use super::object::PyObject;
-use super::exc;
+use super::{exc, PyList};
 use ffi::{self, Py_ssize_t};
 use conversion::{ToPyObject, ExtractPyObject};
-use std::slice;
+use std::{fmt, ptr, slice};
 
 /// Represents a Python tuple object.
 pub struct PyTuple(PyObject);
@@ -43,6 +43,14 @@ impl PyTuple {
         }
     }
 
+    /// Construct a new tuple with the given elements, reusing a tuple from `pool` if possible.
+    ///
+    /// Panics if `elements.len()` differs from the tuple size of the pool.
+    #[inline]
+    pub fn new_pooled(py: Python, pool: &mut PyTuplePool, elements: &[PyObject]) -> PyTuple {
+        pool.get(py, elements)
+    }
+
     /// Retrieves the empty tuple.
     pub fn empty(py: Python) -> PyTuple {
         unsafe {
@@ -71,6 +79,47 @@ impl PyTuple {
         }
     }
 
+    /// Gets the item at the specified index,
+    /// or `None` if the index is out of range.
+    pub fn get(&self, py: Python, index: usize) -> Option<PyObject> {
+        if index < self.len(py) {
+            Some(self.get_item(py, index))
+        } else {
+            None
+        }
+    }
+
+    /// Unpacks the tuple into a fixed-size array of its items.
+    ///
+    /// Returns a `ValueError` if the length of the tuple differs from the array length.
+    /// Arrays of up to 9 elements are supported.
+    ///
+    /// # Example
+    /// ```
+    /// use cpython::{Python, PyObject, PyTuple, ToPyObject};
+    ///
+    /// let gil = Python::acquire_gil();
+    /// let py = gil.python();
+    /// let t = (1, 2).to_py_object(py);
+    /// let items: [PyObject; 2] = t.unpack(py).unwrap();
+    /// assert_eq!(2, items[1].extract::<i32>(py).unwrap());
+    /// ```
+    pub fn unpack<A>(&self, py: Python) -> PyResult<A> where A: PyTupleArray {
+        let slice = self.as_slice();
+        if slice.len() != A::len() {
+            return Err(wrong_tuple_length(py, self, A::len()));
+        }
+        Ok(A::from_slice(py, slice))
+    }
+
+    /// Creates a new list containing the items of the tuple.
+    /// This is equivalent to the Python expression `list(self)`.
+    pub fn to_list(&self, py: Python) -> PyResult<PyList> {
+        unsafe {
+            err::result_cast_from_owned_ptr(py, ffi::PySequence_List(self.0.as_ptr()))
+        }
+    }
+
     #[inline]
     pub fn as_slice<'a>(&'a self) -> &'a [PyObject] {
         // This is safe because PyObject has the same memory layout as *mut ffi::PyObject,
@@ -97,6 +146,87 @@ impl PyTuple {
     }
 }
 
+/// A cache of tuple objects of a fixed size, for code that repeatedly creates
+/// short-lived tuples (e.g. argument tuples for calls in a tight loop).
+///
+/// Tuples are handed out with `get()` (or `PyTuple::new_pooled()`) and handed back with `put()`.
+/// A tuple is only reused if the pool holds the last reference to it,
+/// so tuples that escaped into Python code are never modified.
+/// The items of returned tuples are released immediately, not when the tuple is reused.
+///
+/// Using a pool is entirely optional; `PyTuple::new()` never uses one.
+pub struct PyTuplePool {
+    size: usize,
+    capacity: usize,
+    free: Vec<PyTuple>
+}
+
+impl PyTuplePool {
+    /// Creates a pool for tuples of length `size`, keeping at most `capacity` free tuples.
+    pub fn new(size: usize, capacity: usize) -> PyTuplePool {
+        PyTuplePool { size: size, capacity: capacity, free: Vec::with_capacity(capacity) }
+    }
+
+    /// Gets the length of the tuples managed by this pool.
+    #[inline]
+    pub fn tuple_size(&self) -> usize {
+        self.size
+    }
+
+    /// Gets the number of free tuples currently held by the pool.
+    #[inline]
+    pub fn free_count(&self) -> usize {
+        self.free.len()
+    }
+
+    /// Gets a tuple with the given elements, reusing a free tuple if one is available.
+    ///
+    /// Panics if `elements.len()` differs from the tuple size of the pool.
+    pub fn get(&mut self, py: Python, elements: &[PyObject]) -> PyTuple {
+        assert_eq!(self.size, elements.len());
+        match self.free.pop() {
+            Some(t) => {
+                unsafe {
+                    let ptr = t.0.as_ptr();
+                    for (i, e) in elements.iter().enumerate() {
+                        // the slot is empty (see put()), so nothing is released here
+                        ffi::PyTuple_SetItem(ptr, i as Py_ssize_t, e.steal_ptr(py));
+                    }
+                    // The tuple was untracked in put(); track it again so that
+                    // reference cycles through the new items can be collected.
+                    ffi::PyObject_GC_Track(ptr as *mut _);
+                }
+                t
+            }
+            None => PyTuple::new(py, elements)
+        }
+    }
+
+    /// Returns a tuple to the pool.
+    ///
+    /// The tuple is kept for reuse only if it has the pool's size, the pool isn't full,
+    /// and no other references to the tuple exist; otherwise it is simply released.
+    pub fn put(&mut self, py: Python, t: PyTuple) {
+        if t.len(py) != self.size || self.free.len() >= self.capacity
+            || t.0.get_refcnt(py) != 1 {
+            t.release_ref(py);
+            return;
+        }
+        unsafe {
+            let ptr = t.0.as_ptr();
+            for i in 0 .. self.size {
+                // PyTuple_SetItem releases the old item
+                ffi::PyTuple_SetItem(ptr, i as Py_ssize_t, ptr::null_mut());
+            }
+            // The garbage collector may already have untracked the tuple
+            // (if it only contained atomic items); PyObject_GC_UnTrack is a no-op then.
+            // Either way, get() can then safely track it again after refilling it.
+            ffi::PyObject_GC_UnTrack(ptr as *mut _);
+        }
+        self.free.push(t);
+    }
+}
+
 impl ::std::ops::Index<usize> for PyTuple {
     type Output = PyObject;
 
@@ -151,11 +281,94 @@ impl <'a, 'p> ExactSizeIterator for PyTupleIterator<'a, 'p> {
     }
 }
 
+/// Fixed-size arrays of `PyObject` that can be produced by `PyTuple::unpack()`.
+pub trait PyTupleArray : Sized {
+    #[doc(hidden)]
+    fn len() -> usize;
+
+    #[doc(hidden)]
+    fn from_slice(py: Python, items: &[PyObject]) -> Self;
+}
+
+macro_rules! tuple_array ({$length:expr, $($n:expr),*} => (
+    impl PyTupleArray for [PyObject; $length] {
+        #[inline]
+        fn len() -> usize {
+            $length
+        }
+
+        fn from_slice(py: Python, items: &[PyObject]) -> Self {
+            [$( items[$n].clone_ref(py) ),*]
+        }
+    }
+));
+
+tuple_array!(1, 0);
+tuple_array!(2, 0, 1);
+tuple_array!(3, 0, 1, 2);
+tuple_array!(4, 0, 1, 2, 3);
+tuple_array!(5, 0, 1, 2, 3, 4);
+tuple_array!(6, 0, 1, 2, 3, 4, 5);
+tuple_array!(7, 0, 1, 2, 3, 4, 5, 6);
+tuple_array!(8, 0, 1, 2, 3, 4, 5, 6, 7);
+tuple_array!(9, 0, 1, 2, 3, 4, 5, 6, 7, 8);
+
 fn wrong_tuple_length(py: Python, t: &PyTuple, expected_length: usize) -> PyErr {
     let msg = format!("Expected tuple of length {}, but got tuple of length {}.", expected_length, t.len(py));
     PyErr::new_lazy_init(py.get_type::<exc::ValueError>(), Some(msg.to_py_object(py).into_object()))
 }
 
+/// Generates a constructor `from_py_tuple(py: Python, t: &PyTuple) -> PyResult<Self>`
+/// for a struct, which extracts the tuple elements into the struct fields (in declaration order).
+///
+/// Syntax: `py_struct_from_tuple!(StructName { field1: Type1, field2: Type2, ... })`
+///
+/// The struct itself must be declared separately with the same fields.
+/// The field types must implement `ExtractPyObject`.
+/// A tuple of the wrong length results in a `ValueError`;
+/// if a field cannot be extracted, a `TypeError` naming the field is returned.
+///
+/// # Example
+/// ```
+/// #[macro_use] extern crate cpython;
+/// use cpython::{Python, PyTuple};
+///
+/// struct Point { x: i32, y: i32 }
+/// py_struct_from_tuple!(Point { x: i32, y: i32 });
+///
+/// fn main() {
+///     let gil = Python::acquire_gil();
+///     let py = gil.python();
+///     let t = py.eval("(1, 2)", None, None).unwrap().cast_into::<PyTuple>(py).unwrap();
+///     let p = Point::from_py_tuple(py, &t).unwrap();
+///     assert_eq!((p.x, p.y), (1, 2));
+/// }
+/// ```
+#[macro_export]
+macro_rules! py_struct_from_tuple {
+    ($name:ident { $( $field:ident : $ftype:ty ),+ }) => {
+        impl $name {
+            /// Extracts the struct from a Python tuple.
+            pub fn from_py_tuple(py: $crate::Python, t: &$crate::PyTuple) -> $crate::PyResult<$name> {
+                let fields: &[&str] = &[ $( stringify!($field) ),+ ];
+                if t.len(py) != fields.len() {
+                    return Err($crate::_detail::struct_tuple_length_error(
+                        py, t, fields.len(), stringify!($name)));
+                }
+                let mut iter = t.as_slice().iter();
+                $(
+                    let $field = match iter.next().unwrap().extract::<$ftype>(py) {
+                        Ok(v) => v,
+                        Err(e) => return Err($crate::_detail::struct_field_error(
+                            py, e, stringify!($name), stringify!($field)))
+                    };
+                )+
+                Ok($name { $( $field: $field ),+ })
+            }
+        }
+    }
+}
+
 macro_rules! id (($a:expr) => ($a));
 
 macro_rules! tuple_conversion ({$length:expr,$(($refN:ident, $n:tt, $T:ident)),+} => (
@@ -175,18 +388,28 @@ macro_rules! tuple_conversion ({$length:expr,$(($refN:ident, $n:tt, $T:ident)),+
         }
     }
 
-    /*TODO: reimplement this without slice matching
-    impl <'p, 's, $($T: FromPyObject<'p, 's>),+> FromPyObject<'p, 's> for ($($T,)+) {
-        fn from_py_object(s : &'s PyObject<'p>) -> PyResult<'p, ($($T,)+)> {
-            let t = try!(s.cast_as::<PyTuple>());
-            match t.as_slice() {
-                [$(ref $refN,)+] => Ok((
-                    $(try!($refN.extract::<$T>()),)+
-                )),
-                _ => Err(wrong_tuple_length(t, 2))
+    impl <'prepared, $($T),+> ExtractPyObject<'prepared> for ($($T,)+)
+        where $($T: ExtractPyObject<'prepared>),+
+    {
+        type Prepared = ($($T::Prepared,)+);
+
+        fn prepare_extract(py: Python, obj: &PyObject) -> PyResult<Self::Prepared> {
+            let t = try!(obj.cast_as::<PyTuple>(py));
+            let slice = t.as_slice();
+            if slice.len() != $length {
+                return Err(wrong_tuple_length(py, t, $length));
             }
+            Ok((
+                $(try!(<$T as ExtractPyObject>::prepare_extract(py, &slice[$n])),)+
+            ))
         }
-    }*/
+
+        fn extract(py: Python, prepared: &'prepared Self::Prepared) -> PyResult<Self> {
+            Ok((
+                $(try!(<$T as ExtractPyObject>::extract(py, id!(&prepared.$n))),)+
+            ))
+        }
+    }
 ));
 
 tuple_conversion!(1, (ref0, 0, A));
@@ -239,12 +462,31 @@ extract!(obj to NoArgs; py => {
     }
 });
 
+/// Wrapper that converts a Rust `Result` into a Python `(value, error)` tuple,
+/// for interoperating with Python APIs that report errors this way instead of raising.
+///
+/// `Ok(v)` is converted to `(v, None)`; `Err(e)` is converted to `(None, str(e))`.
+/// Unlike returning a `PyResult`, no exception is raised for the error case.
+#[derive(Copy, Clone, Debug)]
+pub struct ResultTuple<T, E>(pub Result<T, E>);
 
+impl <T, E> ToPyObject for ResultTuple<T, E> where T: ToPyObject, E: fmt::Display {
+    type ObjectType = PyTuple;
+
+    fn to_py_object(&self, py: Python) -> PyTuple {
+        match self.0 {
+            Ok(ref v) => PyTuple::new(py, &[v.to_py_object(py).into_object(), py.None()]),
+            Err(ref e) => PyTuple::new(py, &[py.None(), e.to_string().to_py_object(py).into_object()])
+        }
+    }
+}
 
 #[cfg(test)]
 mod test {
-    use python::{Python, PythonObject};
+    use python::{Python, PythonObject, PyClone};
     use conversion::ToPyObject;
+    use objectprotocol::ObjectProtocol;
+    use objects::{exc, PyTuple};
 
     #[test]
     fn test_len() {
@@ -253,5 +495,139 @@ mod test {
         let tuple = (1, 2, 3).to_py_object(py);
         assert_eq!(3, tuple.len(py));
     }
-}
 
+    #[test]
+    fn test_get() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let tuple = (1, 2, 3).to_py_object(py);
+        assert_eq!(3, tuple.get(py, 2).unwrap().extract::<i32>(py).unwrap());
+        assert!(tuple.get(py, 3).is_none());
+        assert!(PyTuple::empty(py).get(py, 0).is_none());
+    }
+
+    #[test]
+    fn test_unpack() {
+        use objects::PyObject;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let tuple = (1, "two", 3.0).to_py_object(py);
+        let items: [PyObject; 3] = tuple.unpack(py).unwrap();
+        assert_eq!(1, items[0].extract::<i32>(py).unwrap());
+        assert_eq!("two", items[1].extract::<String>(py).unwrap());
+        assert_eq!(3.0, items[2].extract::<f64>(py).unwrap());
+
+        let err = tuple.unpack::<[PyObject; 2]>(py).err().unwrap();
+        assert!(err.matches(py, py.get_type::<exc::ValueError>().as_object()));
+    }
+
+    #[test]
+    fn test_result_tuple() {
+        use super::ResultTuple;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let ok: Result<i32, String> = Ok(42);
+        let tuple = ResultTuple(ok).to_py_object(py);
+        assert_eq!(2, tuple.len(py));
+        assert_eq!(42, tuple.get_item(py, 0).extract::<i32>(py).unwrap());
+        assert!(tuple.get_item(py, 1) == py.None());
+
+        let err: Result<i32, String> = Err("failed".to_owned());
+        let tuple = ResultTuple(err).to_py_object(py);
+        assert_eq!(2, tuple.len(py));
+        assert!(tuple.get_item(py, 0) == py.None());
+        assert_eq!("failed", tuple.get_item(py, 1).extract::<String>(py).unwrap());
+    }
+
+    struct Record {
+        id: i64,
+        name: String,
+        value: f64
+    }
+
+    py_struct_from_tuple!(Record { id: i64, name: String, value: f64 });
+
+    #[test]
+    fn test_struct_from_tuple() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let t = (42i64, "answer", 1.5f64).to_py_object(py);
+        let r = Record::from_py_tuple(py, &t).unwrap();
+        assert_eq!(42, r.id);
+        assert_eq!("answer", r.name);
+        assert_eq!(1.5, r.value);
+
+        let t = (42i64, "answer").to_py_object(py);
+        assert!(Record::from_py_tuple(py, &t).is_err());
+
+        let t = (42i64, 7i32, 1.5f64).to_py_object(py);
+        let mut err = Record::from_py_tuple(py, &t).err().unwrap();
+        assert!(err.matches(py, py.get_type::<exc::TypeError>().as_object()));
+        let msg = err.instance(py).str(py).unwrap().to_string_lossy(py).into_owned();
+        assert!(msg.contains("'name'"));
+    }
+
+    #[test]
+    fn test_extract() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let tuple = (1, "two", 3.0).to_py_object(py).into_object();
+        let (a, b, c): (i32, String, f64) = tuple.extract(py).unwrap();
+        assert_eq!((1, "two".to_owned(), 3.0), (a, b, c));
+        assert!(tuple.extract::<(i32, String)>(py).is_err());
+        assert!(tuple.extract::<(i32, i32, f64)>(py).is_err());
+    }
+
+    #[test]
+    fn test_tuple_pool() {
+        use objects::{PyTuple, PyTuplePool};
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut pool = PyTuplePool::new(2, 4);
+        let a = 1.to_py_object(py).into_object();
+        let b = "b".to_py_object(py).into_object();
+        let t = PyTuple::new_pooled(py, &mut pool, &[a.clone_ref(py), b.clone_ref(py)]);
+        let ptr = t.as_object().as_ptr();
+        let a_refcnt = a.get_refcnt(py);
+        pool.put(py, t);
+        assert_eq!(1, pool.free_count());
+        // the pool does not keep the items alive
+        assert_eq!(a_refcnt - 1, a.get_refcnt(py));
+
+        for i in 0 .. 1000 {
+            let t = pool.get(py, &[i.to_py_object(py).into_object(), b.clone_ref(py)]);
+            // the same tuple object is reused every time
+            assert_eq!(ptr, t.as_object().as_ptr());
+            assert_eq!(i, t.get_item(py, 0).extract::<i32>(py).unwrap());
+            assert!(t.get_item(py, 1) == b);
+            pool.put(py, t);
+        }
+
+        // a tuple that is still referenced elsewhere is not reused
+        let t = pool.get(py, &[a.clone_ref(py), b.clone_ref(py)]);
+        let t2 = t.clone_ref(py);
+        pool.put(py, t);
+        assert_eq!(0, pool.free_count());
+        assert_eq!(1, t2.get_item(py, 0).extract::<i32>(py).unwrap());
+    }
+
+    #[test]
+    fn test_tuple_pool_gc_tracking() {
+        use objects::{PyTuplePool, PyList, NoArgs};
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let gc = py.import("gc").unwrap();
+        let mut pool = PyTuplePool::new(1, 1);
+        let t = pool.get(py, &[1.to_py_object(py).into_object()]);
+        // a collection untracks tuples that only contain atomic items
+        gc.call(py, "collect", NoArgs, None).unwrap();
+        assert!(!gc.call(py, "is_tracked", (&t,), None).unwrap().extract::<bool>(py).unwrap());
+        let ptr = t.as_object().as_ptr();
+        pool.put(py, t);
+        let list = PyList::new(py, &[]);
+        let t = pool.get(py, &[list.into_object()]);
+        assert_eq!(ptr, t.as_object().as_ptr());
+        // the reused tuple holds a container, so it must be tracked again
+        assert!(gc.call(py, "is_tracked", (&t,), None).unwrap().extract::<bool>(py).unwrap());
+    }
+}
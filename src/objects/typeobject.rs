@@ -16,11 +16,13 @@
 // OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-use python::{Python, PythonObject, ToPythonPointer};
+use python::{Python, PythonObject, ToPythonPointer, PyClone};
 use conversion::ToPyObject;
 use objects::{PyObject, PyTuple, PyDict};
 use err::{PyResult, result_from_owned_ptr};
 use ffi;
+use std::borrow::Cow;
+use std::ffi::CStr;
 
 /// Represents a reference to a Python type object.
 pub struct PyType(PyObject);
@@ -42,6 +44,27 @@ impl PyType {
         PyObject::from_borrowed_ptr(py, p as *mut ffi::PyObject).unchecked_cast_into::<PyType>()
     }
 
+    /// Gets the name of the type, as stored in `tp_name`.
+    ///
+    /// For heap types this is the plain class name;
+    /// types defined in C may use a dotted name like `"module.Name"`.
+    pub fn name<'a>(&'a self, _py: Python) -> Cow<'a, str> {
+        unsafe {
+            CStr::from_ptr((*self.as_type_ptr()).tp_name).to_string_lossy()
+        }
+    }
+
+    /// Gets the method resolution order of the type.
+    /// This is equivalent to the Python expression `self.__mro__`.
+    pub fn mro(&self, py: Python) -> PyTuple {
+        unsafe {
+            match PyObject::from_borrowed_ptr_opt(py, (*self.as_type_ptr()).tp_mro) {
+                Some(mro) => mro.unchecked_cast_into::<PyTuple>(),
+                None => PyTuple::empty(py)
+            }
+        }
+    }
+
     /// Return true if `self` is a subtype of `b`.
     #[inline]
     pub fn is_subtype_of(&self, _: Python, b : &PyType) -> bool {
@@ -64,6 +87,23 @@ impl PyType {
             result_from_owned_ptr(py, ffi::PyObject_Call(self.0.as_ptr(), args, kwargs.as_ptr()))
         })
     }
+
+    /// Creates a new instance without calling `__init__`, by invoking
+    /// `copyreg.__newobj__(self, *args)` (`copy_reg` in Python 2).
+    ///
+    /// This is the constructor used by pickle protocol 2 and higher;
+    /// `__reduce__` implementations can use it to rebuild Rust-backed objects.
+    pub fn newobj(&self, py: Python, args: &PyTuple) -> PyResult<PyObject> {
+        static mut COPYREG: *mut ffi::PyObject = 0 as *mut ffi::PyObject;
+        let name = if cfg!(feature="python27-sys") { "copy_reg" } else { "copyreg" };
+        let copyreg = try!(unsafe { ::python::import_cached(py, &mut COPYREG, name) });
+        let mut newobj_args = Vec::with_capacity(args.len(py) + 1);
+        newobj_args.push(self.0.clone_ref(py));
+        for arg in args.as_slice() {
+            newobj_args.push(arg.clone_ref(py));
+        }
+        copyreg.call(py, "__newobj__", PyTuple::new(py, &newobj_args), None)
+    }
 }
 
 impl PartialEq for PyType {
@@ -74,3 +114,45 @@ impl PartialEq for PyType {
 }
 impl Eq for PyType { }
 
+#[cfg(test)]
+mod test {
+    use python::{Python, PythonObject};
+    use objects::PyObject;
+
+    #[test]
+    fn test_list_type() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let list = py.eval("[1, 2]", None, None).unwrap();
+        let ty = list.get_type();
+        assert_eq!("list", ty.name(py));
+        let object_type = py.get_type::<PyObject>();
+        assert!(ty.is_subtype_of(py, &object_type));
+        assert!(!object_type.is_subtype_of(py, ty));
+        let mro = ty.mro(py);
+        assert_eq!(2, mro.len(py));
+        assert!(mro.get_item(py, 0) == *ty.as_object());
+        assert!(mro.get_item(py, 1) == *object_type.as_object());
+    }
+
+    #[test]
+    fn test_newobj() {
+        use objects::PyDict;
+        use conversion::ToPyObject;
+        use objectprotocol::ObjectProtocol;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let d = PyDict::new(py);
+        py.run("class Point(object):\n\
+                \x20   def __new__(cls, x):\n\
+                \x20       self = object.__new__(cls)\n\
+                \x20       self.x = x\n\
+                \x20       return self\n\
+                \x20   def __init__(self, x):\n\
+                \x20       raise AssertionError('__init__ must not be called')\n", None, Some(&d)).unwrap();
+        let ty = py.eval("Point", None, Some(&d)).unwrap().cast_into::<super::PyType>(py).unwrap();
+        let obj = ty.newobj(py, &(5,).to_py_object(py)).unwrap();
+        assert!(ty.is_instance(py, &obj));
+        assert_eq!(5, obj.getattr(py, "x").unwrap().extract::<i32>(py).unwrap());
+    }
+}
@@ -0,0 +1,93 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Conversions between `uuid::Uuid` and Python `uuid.UUID` objects.
+//! Requires the `uuid` feature.
+
+use uuid::Uuid;
+use ffi;
+use python::{self, Python, PythonObject};
+use err::{PyErr, PyResult};
+use conversion::{ExtractPyObject, ToPyObject};
+use objectprotocol::ObjectProtocol;
+use super::{exc, PyObject, PyBytes, PyString, PyModule};
+
+static mut UUID_MODULE: *mut ffi::PyObject = 0 as *mut ffi::PyObject;
+
+fn uuid_module(py: Python) -> PyResult<PyModule> {
+    unsafe { python::import_cached(py, &mut UUID_MODULE, "uuid") }
+}
+
+/// Converts a `Uuid` to a Python `uuid.UUID` instance.
+///
+/// Panics if the Python `uuid` module cannot be imported.
+impl ToPyObject for Uuid {
+    type ObjectType = PyObject;
+
+    fn to_py_object(&self, py: Python) -> PyObject {
+        let bytes = PyBytes::new(py, self.as_bytes());
+        uuid_module(py)
+            .and_then(|m| m.call(py, "UUID", (py.None(), bytes), None))
+            .expect("failed to create uuid.UUID")
+    }
+}
+
+/// Extracts a `Uuid` from a Python `uuid.UUID` instance,
+/// or from a string in any format accepted by `Uuid::parse_str`.
+extract!(obj to Uuid; py => {
+    if let Ok(s) = PyString::extract(py, obj) {
+        return Uuid::parse_str(&s).map_err(|_| PyErr::new::<exc::ValueError, _>(py,
+            format!("invalid UUID string: '{}'", s)));
+    }
+    let bytes = try!(try!(obj.getattr(py, "bytes")).cast_into::<PyBytes>(py));
+    Uuid::from_bytes(bytes.as_slice(py)).map_err(|_| PyErr::new::<exc::ValueError, _>(py,
+        "UUID must have 16 bytes"))
+});
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+    use python::{Python, PythonObject};
+    use conversion::ToPyObject;
+    use objectprotocol::ObjectProtocol;
+
+    #[test]
+    fn test_round_trip() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let uuid = Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap();
+        let obj = uuid.to_py_object(py);
+        let s: String = obj.str(py).unwrap().into_object().extract(py).unwrap();
+        assert_eq!("67e55044-10b1-426f-9247-bb680e5fe0c8", s);
+        assert_eq!(uuid, obj.extract::<Uuid>(py).unwrap());
+    }
+
+    #[test]
+    fn test_extract() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let expected = Uuid::parse_str("12345678-1234-5678-1234-567812345678").unwrap();
+        let obj = py.eval("__import__('uuid').UUID('{12345678-1234-5678-1234-567812345678}')",
+                          None, None).unwrap();
+        assert_eq!(expected, obj.extract::<Uuid>(py).unwrap());
+        let s = "12345678-1234-5678-1234-567812345678".to_py_object(py).into_object();
+        assert_eq!(expected, s.extract::<Uuid>(py).unwrap());
+        let s = "not a uuid".to_py_object(py).into_object();
+        assert!(s.extract::<Uuid>(py).is_err());
+    }
+}
@@ -19,11 +19,15 @@
 use std;
 use std::ffi::CString;
 use std::marker::PhantomData;
-use libc::c_int;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use libc::{c_int, c_long};
 use ffi;
-use objects::{PyObject, PyType, PyBool, PyDict, PyModule};
+use conversion::ToPyObject;
+use objects::{exc, PyObject, PyType, PyBool, PyDict, PyModule, PyTuple, PyIterator, NoArgs};
 use err::{self, PyErr, PyResult};
-use pythonrun::GILGuard;
+use pythonrun::{GILGuard, BorrowToken};
 
 /// Marker type that indicates that the GIL is currently held.
 ///
@@ -38,6 +42,17 @@ use pythonrun::GILGuard;
 #[derive(Copy, Clone)]
 pub struct Python<'p>(PhantomData<&'p GILGuard>);
 
+/// Selects the kind of code accepted by `Python::compile()`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CompileMode {
+    /// A single expression (like Python's `eval()`).
+    Eval,
+    /// A sequence of statements (like Python's `exec()`).
+    Exec,
+    /// A single interactive statement; expression values are printed.
+    Single
+}
+
 /// Trait implemented by all Python object types.
 pub trait PythonObject : ::conversion::ToPyObject + Sized + 'static {
     /// Casts the Python object to PyObject.
@@ -73,7 +88,12 @@ pub trait PythonObjectWithTypeObject : PythonObjectWithCheckedDowncast {
     fn type_object(Python) -> PyType;
 }
 
+/// Explicit cloning of Python object handles.
+///
+/// `PyObject` deliberately does not implement `Clone`: incrementing the reference count
+/// requires holding the GIL, so the `Python` token is part of the signature.
 pub trait PyClone : Sized {
+    /// Creates a new owned handle to the same object, incrementing the reference count.
     fn clone_ref(&self, Python) -> Self;
 }
 
@@ -106,6 +126,8 @@ impl <T> PyDrop for T where T: PythonObject {
     fn release_ref(self, _py: Python) {
         let ptr = self.into_object().steal_ptr();
         unsafe {
+            debug_assert!(ffi::Py_REFCNT(ptr) > 0,
+                "releasing a PyObject whose reference count already is zero");
             ffi::Py_DECREF(ptr);
         }
     }
@@ -196,10 +218,78 @@ impl<'p> Python<'p> {
         GILGuard::acquire()
     }
 
+    /// Acquires the global interpreter lock from a thread with unknown Python thread state,
+    /// without initializing the Python runtime.
+    ///
+    /// See [GILGuard::ensure()](struct.GILGuard.html#method.ensure) for when to use this
+    /// instead of `acquire_gil()`.
+    #[inline]
+    pub fn ensure_gil() -> GILGuard {
+        GILGuard::ensure()
+    }
+
+    /// Returns true if the Python interpreter is initialized.
+    #[inline]
+    pub fn is_initialized() -> bool {
+        unsafe { ffi::Py_IsInitialized() != 0 }
+    }
+
+    /// Initializes the Python interpreter if it is not already initialized.
+    ///
+    /// This is the same as [prepare_freethreaded_python()](fn.prepare_freethreaded_python.html);
+    /// `acquire_gil()` also calls it automatically.
+    #[inline]
+    pub fn initialize() {
+        ::pythonrun::prepare_freethreaded_python()
+    }
+
+    /// Shuts down the Python interpreter, like `Py_Finalize()`.
+    /// Does nothing if the interpreter is not initialized.
+    ///
+    /// This is dangerous: the current thread must not hold the GIL, no other thread
+    /// may use Python concurrently or afterwards, and all remaining `PyObject`s must
+    /// have been leaked (dropping them after finalization is undefined behavior).
+    /// The interpreter cannot be initialized again by this crate after finalization.
+    pub unsafe fn finalize() {
+        if ffi::Py_IsInitialized() != 0 {
+            // Py_Finalize() must be called with the GIL held;
+            // the thread state is destroyed by the finalization, so it is never released.
+            ffi::PyGILState_Ensure();
+            ffi::Py_Finalize();
+        }
+    }
+
+    /// Returns true if the current thread holds the GIL.
+    #[inline]
+    pub fn holds_gil() -> bool {
+        unsafe { ffi::PyGILState_Check() != 0 }
+    }
+
+    /// Panics if the current thread does not hold the GIL.
+    ///
+    /// This can be used at the top of functions that require the GIL
+    /// but cannot take a `Python` token (e.g. callbacks from C code),
+    /// so that contract violations are caught early during testing.
+    #[inline]
+    pub fn assert_holds_gil() {
+        assert!(Python::holds_gil(), "the current thread does not hold the GIL");
+    }
+
+    /// Creates a `BorrowToken` marking that the current code holds borrowed Python data.
+    /// While the token is alive, `allow_threads()` panics in debug builds.
+    #[inline]
+    pub fn borrow_token(self) -> BorrowToken {
+        BorrowToken::new()
+    }
+
     /// Temporarily releases the GIL, thus allowing other Python threads to run.
+    ///
+    /// In debug builds, panics if a `BorrowToken` is alive on the current thread.
     pub fn allow_threads<T, F>(self, f: F) -> T where F : Send + FnOnce() -> T {
         // The `Send` bound on the closure prevents the user from
         // transferring the `Python` token into the closure.
+        debug_assert!(BorrowToken::live_count() == 0,
+            "allow_threads() called while {} BorrowToken(s) are alive", BorrowToken::live_count());
         unsafe {
             let save = ffi::PyEval_SaveThread();
             let result = f();
@@ -239,28 +329,69 @@ impl<'p> Python<'p> {
         let code = CString::new(code).unwrap();
 
         unsafe {
-            let mptr = ffi::PyImport_AddModule("__main__\0".as_ptr() as *const _);
+            let (globals, locals) = try!(self.resolve_context(globals, locals));
+            let res_ptr = ffi::PyRun_StringFlags(code.as_ptr(),
+                start, globals, locals, 0 as *mut _);
 
-            if mptr.is_null() {
-                return Err(PyErr::fetch(self));
-            }
+            err::result_from_owned_ptr(self, res_ptr)
+        }
+    }
 
-            let mdict = ffi::PyModule_GetDict(mptr);
+    /// Returns the raw globals and locals dicts to use for running code.
+    unsafe fn resolve_context(self, globals: Option<&PyDict>, locals: Option<&PyDict>)
+                              -> PyResult<(*mut ffi::PyObject, *mut ffi::PyObject)> {
+        let mptr = ffi::PyImport_AddModule("__main__\0".as_ptr() as *const _);
 
-            let globals = match globals {
-                Some(g) => g.as_ptr(),
-                None => mdict,
-            };
+        if mptr.is_null() {
+            return Err(PyErr::fetch(self));
+        }
 
-            let locals = match locals {
-                Some(l) => l.as_ptr(),
-                None => globals
-            };
+        let mdict = ffi::PyModule_GetDict(mptr);
 
-            let res_ptr = ffi::PyRun_StringFlags(code.as_ptr(),
-                start, globals, locals, 0 as *mut _);
+        let globals = match globals {
+            Some(g) => g.as_ptr(),
+            None => mdict,
+        };
 
-            err::result_from_owned_ptr(self, res_ptr)
+        let locals = match locals {
+            Some(l) => l.as_ptr(),
+            None => globals
+        };
+        Ok((globals, locals))
+    }
+
+    /// Compiles Python source code into a code object,
+    /// which can be executed repeatedly using `eval_code()`.
+    ///
+    /// `filename` is used in tracebacks and in the `SyntaxError` raised for invalid code.
+    pub fn compile(self, code: &str, filename: &str, mode: CompileMode) -> PyResult<PyObject> {
+        let code = CString::new(code).unwrap();
+        let filename = CString::new(filename).unwrap();
+        let start = match mode {
+            CompileMode::Eval => ffi::Py_eval_input,
+            CompileMode::Exec => ffi::Py_file_input,
+            CompileMode::Single => ffi::Py_single_input
+        };
+        unsafe {
+            err::result_from_owned_ptr(self,
+                ffi::Py_CompileString(code.as_ptr(), filename.as_ptr(), start))
+        }
+    }
+
+    /// Executes a code object (as returned by `compile()`) in the given context.
+    /// For code compiled with `CompileMode::Eval`, returns the value of the expression.
+    ///
+    /// If `globals` is `None`, it defaults to Python module `__main__`.
+    /// If `locals` is `None`, it defaults to the value of `globals`.
+    pub fn eval_code(self, code: &PyObject, globals: Option<&PyDict>,
+                     locals: Option<&PyDict>) -> PyResult<PyObject> {
+        unsafe {
+            if ffi::PyCode_Check(code.as_ptr()) == 0 {
+                return Err(PyErr::new::<exc::TypeError, _>(self, "expected a code object"));
+            }
+            let (globals, locals) = try!(self.resolve_context(globals, locals));
+            err::result_from_owned_ptr(self,
+                ffi::PyEval_EvalCode(code.as_ptr() as *mut _, globals, locals))
         }
     }
 
@@ -294,6 +425,75 @@ impl<'p> Python<'p> {
     pub fn import(self, name : &str) -> PyResult<PyModule> {
         PyModule::import(self, name)
     }
+
+    /// Issues a warning of the given category (e.g. `exc::DeprecationWarning`),
+    /// like the Python function `warnings.warn(message, category, stacklevel)`.
+    ///
+    /// `stacklevel` 1 attributes the warning to the Python code calling into Rust.
+    /// The warning filters may turn the warning into an exception,
+    /// in which case that exception is returned as `Err`.
+    pub fn warn(self, category: &PyObject, message: &str, stacklevel: i32) -> PyResult<()> {
+        let message = CString::new(message).unwrap();
+        unsafe {
+            err::error_on_minusone(self,
+                ffi::PyErr_WarnEx(category.as_ptr(), message.as_ptr(), stacklevel as ffi::Py_ssize_t))
+        }
+    }
+
+    /// Calls `callable(*args)`, interrupting the call by raising `KeyboardInterrupt`
+    /// in the current thread if it is still running after `timeout`.
+    ///
+    /// The interrupt is delivered by a watchdog thread using `PyThreadState_SetAsyncExc`.
+    /// This is best-effort: the exception is only raised when the interpreter
+    /// executes Python bytecode, so long-running C code (including blocking I/O
+    /// and `time.sleep()`) is not interrupted, and Python code may catch the exception.
+    /// The watchdog thread is stopped and joined before this function returns.
+    pub fn run_with_timeout<A>(self, callable: &PyObject, args: A, timeout: Duration) -> PyResult<PyObject>
+        where A: ToPyObject<ObjectType=PyTuple>
+    {
+        use objectprotocol::ObjectProtocol;
+        #[cfg(feature="python27-sys")]
+        const THREAD_MODULE: &'static str = "thread";
+        #[cfg(feature="python3-sys")]
+        const THREAD_MODULE: &'static str = "_thread";
+
+        let thread_id: c_long = try!(try!(self.import(THREAD_MODULE)).call(self, "get_ident", NoArgs, None)).extract(self));
+        // (finished, interrupted); only modified while holding the GIL
+        let state = Arc::new(Mutex::new((false, false)));
+        let watchdog_state = state.clone();
+        let (done_sender, done_receiver) = mpsc::channel::<()>();
+        let watchdog = thread::spawn(move || {
+            if let Err(mpsc::RecvTimeoutError::Timeout) = done_receiver.recv_timeout(timeout) {
+                let _gil = Python::acquire_gil();
+                let mut state = watchdog_state.lock().unwrap();
+                if !state.0 {
+                    unsafe { ffi::PyThreadState_SetAsyncExc(thread_id, ffi::PyExc_KeyboardInterrupt) };
+                    state.1 = true;
+                }
+            }
+        });
+        let result = callable.call(self, args, None);
+        state.lock().unwrap().0 = true;
+        drop(done_sender);
+        // The watchdog may be waiting for the GIL, so release it while joining.
+        self.allow_threads(move || watchdog.join()).unwrap();
+        if state.lock().unwrap().1 {
+            // The call may have returned before the exception was raised;
+            // make sure it doesn't leak into unrelated code.
+            unsafe { ffi::PyThreadState_SetAsyncExc(thread_id, std::ptr::null_mut()) };
+        }
+        result
+    }
+
+    /// Creates an iterator that calls `callable` without arguments for each item,
+    /// until it returns a value equal to `sentinel`.
+    /// This is equivalent to the Python expression `iter(callable, sentinel)`.
+    pub fn iter_callable(self, callable: &PyObject, sentinel: &PyObject) -> PyResult<PyIterator<'p>> {
+        let obj = try!(unsafe {
+            err::result_from_owned_ptr(self, ffi::PyCallIter_New(callable.as_ptr(), sentinel.as_ptr()))
+        });
+        Ok(try!(PyIterator::from_object(self, obj)))
+    }
 }
 
 impl <'p> std::fmt::Debug for PythonObjectDowncastError<'p> {
@@ -302,9 +502,24 @@ impl <'p> std::fmt::Debug for PythonObjectDowncastError<'p> {
     }
 }
 
+/// Imports the module `name`, caching it in `cache` so that later calls
+/// don't need to go through the import machinery.
+///
+/// `cache` should be a `static mut` initialized to null.
+/// This is unsafe because the caller must ensure the cache is only accessed
+/// while holding the GIL; the cached reference is never released.
+pub unsafe fn import_cached(py: Python, cache: &mut *mut ffi::PyObject, name: &str) -> PyResult<PyModule> {
+    if cache.is_null() {
+        *cache = try!(py.import(name)).into_object().steal_ptr();
+    }
+    Ok(PyObject::from_borrowed_ptr(py, *cache).unchecked_cast_into::<PyModule>())
+}
+
 #[cfg(test)]
 mod test {
-    use {Python, PyDict};
+    use {Python, PythonObject, PyDict, ObjectProtocol};
+    use objects::exc;
+    use super::CompileMode;
 
     #[test]
     fn test_eval() {
@@ -325,4 +540,116 @@ mod test {
 
         assert_eq!(v, 42);
     }
+
+    #[test]
+    fn test_is_initialized() {
+        let gil = Python::acquire_gil();
+        assert!(Python::is_initialized());
+        drop(gil);
+        Python::initialize();
+        assert!(Python::is_initialized());
+    }
+
+    #[test]
+    fn test_warn() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let category = py.get_type::<exc::DeprecationWarning>().into_object();
+        let catcher = py.eval("__import__('warnings').catch_warnings(record=True)", None, None).unwrap();
+        catcher.with_context(py, |log| {
+            try!(py.run("import warnings; warnings.simplefilter('always')", None, None));
+            assert!(py.warn(&category, "old api", 1).is_ok());
+            assert_eq!(1, try!(log.len(py)));
+            let message = try!(try!(log.get_item(py, 0)).getattr(py, "message"));
+            assert_eq!("old api", try!(message.str(py)).to_string_lossy(py));
+
+            try!(py.run("warnings.simplefilter('error')", None, None));
+            let err = py.warn(&category, "old api", 1).err().unwrap();
+            assert!(err.matches(py, category.as_object()));
+            Ok(())
+        }).unwrap();
+    }
+
+    #[test]
+    fn test_compile() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let code = py.compile("a + b", "<test>", CompileMode::Eval).unwrap();
+
+        let d = PyDict::new(py);
+        d.set_item(py, "a", 1).unwrap();
+        d.set_item(py, "b", 2).unwrap();
+        let v: i32 = py.eval_code(&code, Some(&d), None).unwrap().extract(py).unwrap();
+        assert_eq!(v, 3);
+
+        let d = PyDict::new(py);
+        d.set_item(py, "a", 20).unwrap();
+        d.set_item(py, "b", 22).unwrap();
+        let v: i32 = py.eval_code(&code, Some(&d), None).unwrap().extract(py).unwrap();
+        assert_eq!(v, 42);
+    }
+
+    #[test]
+    fn test_compile_syntax_error() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut err = py.compile("x = (", "test.py", CompileMode::Exec).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::SyntaxError>().as_object()));
+        let instance = err.instance(py);
+        let filename: String = instance.getattr(py, "filename").unwrap().extract(py).unwrap();
+        assert_eq!(filename, "test.py");
+        let lineno: i32 = instance.getattr(py, "lineno").unwrap().extract(py).unwrap();
+        assert_eq!(lineno, 1);
+    }
+
+    #[test]
+    fn test_holds_gil() {
+        {
+            let _gil = Python::acquire_gil();
+            assert!(Python::holds_gil());
+            Python::assert_holds_gil();
+        }
+        // a fresh thread doesn't hold the GIL until it acquires it
+        ::std::thread::spawn(|| {
+            assert!(!Python::holds_gil());
+            let _gil = Python::acquire_gil();
+            assert!(Python::holds_gil());
+        }).join().unwrap();
+    }
+
+    #[test]
+    fn test_run_with_timeout() {
+        use std::time::Duration;
+        use objects::NoArgs;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let d = PyDict::new(py);
+        py.run("def spin():\n    while True:\n        pass\n", None, Some(&d)).unwrap();
+        let spin = d.get_item(py, "spin").unwrap();
+        let err = py.run_with_timeout(&spin, NoArgs, Duration::from_millis(100)).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::KeyboardInterrupt>().as_object()));
+
+        let abs = py.eval("abs", None, None).unwrap();
+        let v = py.run_with_timeout(&abs, (-3,), Duration::from_millis(100)).unwrap();
+        assert_eq!(3, v.extract::<i32>(py).unwrap());
+        // returns without waiting for the timeout, and the stopped watchdog
+        // must not interrupt later code
+        let v = py.run_with_timeout(&abs, (-3,), Duration::from_secs(3600)).unwrap();
+        assert_eq!(3, v.extract::<i32>(py).unwrap());
+        py.run("for i in range(100000): pass", None, None).unwrap();
+    }
+
+    #[test]
+    fn test_iter_callable() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let d = PyDict::new(py);
+        py.run("values = [0, 1, 2, -1, 3]\nf = lambda: values.pop(0)", None, Some(&d)).unwrap();
+        let f = d.get_item(py, "f").unwrap();
+        let sentinel = py.eval("-1", None, None).unwrap();
+        let items: Vec<i32> = py.iter_callable(&f, &sentinel).unwrap()
+            .map(|item| item.unwrap().extract(py).unwrap())
+            .collect();
+        assert_eq!(vec![0, 1, 2], items);
+    }
 }
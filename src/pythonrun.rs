@@ -17,6 +17,9 @@
 // DEALINGS IN THE SOFTWARE.
 
 use std::sync::{Once, ONCE_INIT};
+#[cfg(debug_assertions)]
+use std::cell::Cell;
+use std::marker::PhantomData;
 use ffi;
 use python::Python;
 
@@ -116,6 +119,25 @@ impl GILGuard {
         GILGuard { gstate: gstate }
     }
 
+    /// Acquires the global interpreter lock on a thread whose Python thread state is unknown,
+    /// e.g. in a callback invoked by a C library on a thread not created by Python.
+    ///
+    /// Unlike `acquire()`, this never initializes the Python runtime:
+    /// it panics if the interpreter (or Python threading) is not yet initialized.
+    /// As with `acquire()`, `PyGILState_Ensure()` creates a thread state for the
+    /// current thread if necessary, and the GIL is released when the guard is dropped.
+    ///
+    /// Use `acquire()` in code that may need to start the interpreter
+    /// (e.g. when embedding Python), and `ensure()` in callbacks that require
+    /// an already-running interpreter.
+    pub fn ensure() -> GILGuard {
+        unsafe {
+            assert!(ffi::Py_IsInitialized() != 0, "Python interpreter is not initialized");
+            assert!(ffi::PyEval_ThreadsInitialized() != 0, "Python threading is not initialized");
+            GILGuard { gstate: ffi::PyGILState_Ensure() }
+        }
+    }
+
     /// Retrieves the marker type that proves that the GIL was acquired.
     #[inline]
     pub fn python<'p>(&'p self) -> Python<'p> {
@@ -172,3 +194,98 @@ impl <T> GILProtected<T> {
     }
 }
 
+/// Marks a region of code that holds borrowed Python data which must not be
+/// accessed while the GIL is released.
+///
+/// Created by `Python::borrow_token()`. In debug builds, the number of live tokens
+/// is tracked per thread, and `Python::allow_threads()` panics if any token is alive,
+/// catching code that keeps using Python objects across a GIL release.
+/// In release builds, tokens are not tracked and cost nothing.
+#[must_use]
+pub struct BorrowToken {
+    // tokens are counted per thread, so they must be dropped on the thread that created them
+    _not_send: PhantomData<*mut ()>
+}
+
+#[cfg(debug_assertions)]
+thread_local!(static LIVE_BORROW_TOKENS: Cell<usize> = Cell::new(0));
+
+impl BorrowToken {
+    #[cfg(debug_assertions)]
+    pub fn new() -> BorrowToken {
+        LIVE_BORROW_TOKENS.with(|c| c.set(c.get() + 1));
+        BorrowToken { _not_send: PhantomData }
+    }
+
+    #[cfg(not(debug_assertions))]
+    #[inline]
+    pub fn new() -> BorrowToken {
+        BorrowToken { _not_send: PhantomData }
+    }
+
+    /// Gets the number of live tokens on the current thread.
+    /// Always returns 0 in release builds.
+    #[cfg(debug_assertions)]
+    pub fn live_count() -> usize {
+        LIVE_BORROW_TOKENS.with(|c| c.get())
+    }
+
+    /// Gets the number of live tokens on the current thread.
+    /// Always returns 0 in release builds.
+    #[cfg(not(debug_assertions))]
+    #[inline]
+    pub fn live_count() -> usize {
+        0
+    }
+}
+
+#[cfg(debug_assertions)]
+impl Drop for BorrowToken {
+    fn drop(&mut self) {
+        LIVE_BORROW_TOKENS.with(|c| c.set(c.get() - 1));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::thread;
+    use python::Python;
+    use super::BorrowToken;
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn test_borrow_token_tracking() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let before = BorrowToken::live_count();
+        {
+            let _a = py.borrow_token();
+            let _b = py.borrow_token();
+            assert_eq!(before + 2, BorrowToken::live_count());
+        }
+        assert_eq!(before, BorrowToken::live_count());
+        assert_eq!(3, py.allow_threads(|| 1 + 2));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn test_borrow_token_across_allow_threads() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let _token = py.borrow_token();
+        py.allow_threads(|| ());
+    }
+
+    #[test]
+    fn test_ensure_gil_from_new_thread() {
+        // make sure the interpreter is running
+        drop(Python::acquire_gil());
+        let result = thread::spawn(|| {
+            let gil = Python::ensure_gil();
+            let py = gil.python();
+            py.eval("1 + 1", None, None).unwrap().extract::<i32>(py).unwrap()
+        }).join().unwrap();
+        assert_eq!(2, result);
+    }
+}
@@ -20,9 +20,9 @@ use libc;
 use ffi;
 use python::{Python, ToPythonPointer, PythonObject, PyClone};
 use conversion::ToPyObject;
-use objects::{PyObject, PyType};
+use objects::{PyObject, PyType, exc};
 use std::{mem, ops, ptr, marker};
-use err::{self, PyResult};
+use err::{self, PyErr, PyResult};
 
 pub mod typebuilder;
 pub mod method;
@@ -78,6 +78,44 @@ impl PythonBaseObject for PyObject {
     }
 }
 
+/// Implements Python's subscript syntax (`obj[key]`) for the rust value
+/// stored in a `PyRustObject`.
+///
+/// Use `PyRustTypeBuilder::mapping()` to enable this protocol on a type.
+/// The key is passed through as-is: negative indices and slices
+/// have to be handled by the implementation.
+pub trait PyRustMapping {
+    /// Implements `self[key]`.
+    fn get_item(&self, py: Python, key: &PyObject) -> PyResult<PyObject>;
+
+    /// Implements `self[key] = value`.
+    ///
+    /// The default implementation raises `TypeError`.
+    fn set_item(&self, py: Python, _key: &PyObject, _value: &PyObject) -> PyResult<()> {
+        Err(PyErr::new::<exc::TypeError, _>(py, "object does not support item assignment"))
+    }
+
+    /// Implements `del self[key]`.
+    ///
+    /// The default implementation raises `TypeError`.
+    fn del_item(&self, py: Python, _key: &PyObject) -> PyResult<()> {
+        Err(PyErr::new::<exc::TypeError, _>(py, "object does not support item deletion"))
+    }
+}
+
+/// Implements Python's iterator protocol for the rust value
+/// stored in a `PyRustObject`.
+///
+/// Use `PyRustTypeBuilder::iterator()` to enable this protocol on a type.
+/// `__iter__` returns the object itself, so that it can be used directly
+/// in a Python `for` loop.
+pub trait PyRustIterator {
+    /// Implements `__next__`.
+    ///
+    /// Returning `Ok(None)` ends the iteration (raises `StopIteration`).
+    fn next(&self, py: Python) -> PyResult<Option<PyObject>>;
+}
+
 /// A Python object that contains a rust value of type T,
 /// and is derived from base class B.
 /// Note that this type effectively acts like `Rc<T>`,
@@ -18,7 +18,10 @@
 
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
-use {Python, NoArgs, PythonObject, PyRustTypeBuilder};
+use std::fmt;
+use std::cell::Cell;
+use {Python, NoArgs, PythonObject, PyRustTypeBuilder, ObjectProtocol};
+use {PyObject, PyResult, PyDict, PyRustMapping, PyRustIterator, ToPyObject};
 
 #[test]
 fn rustobject_calls_drop() {
@@ -67,3 +70,95 @@ fn heaptype_refcount() {
     assert_eq!(old_refcnt, t.as_object().get_refcnt(py));
 }
 
+
+
+#[test]
+fn display_and_debug() {
+    #[derive(Debug)]
+    struct Point { x: i32, y: i32 }
+    impl fmt::Display for Point {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "({}, {})", self.x, self.y)
+        }
+    }
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let t = PyRustTypeBuilder::<Point>::new(py, "Point").display().debug().finish().unwrap();
+    let inst = t.create_instance(py, Point { x: 1, y: 2 }, ()).into_object();
+    let s: String = inst.str(py).unwrap().into_object().extract(py).unwrap();
+    assert_eq!("(1, 2)", s);
+    let r: String = inst.repr(py).unwrap().into_object().extract(py).unwrap();
+    assert_eq!("Point { x: 1, y: 2 }", r);
+}
+
+#[test]
+fn display_panic_raises_runtime_error() {
+    use objects::exc;
+    struct Bad;
+    impl fmt::Display for Bad {
+        fn fmt(&self, _f: &mut fmt::Formatter) -> fmt::Result {
+            panic!("formatting failed")
+        }
+    }
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let t = PyRustTypeBuilder::<Bad>::new(py, "Bad").display().finish().unwrap();
+    let inst = t.create_instance(py, Bad, ()).into_object();
+    let err = inst.str(py).err().unwrap();
+    assert!(err.matches(py, py.get_type::<exc::RuntimeError>().as_object()));
+}
+
+#[test]
+fn mapping() {
+    struct Doubler { last_set: Cell<i32> }
+    impl PyRustMapping for Doubler {
+        fn get_item(&self, py: Python, key: &PyObject) -> PyResult<PyObject> {
+            let key: i32 = try!(key.extract(py));
+            Ok((key * 2).to_py_object(py).into_object())
+        }
+
+        fn set_item(&self, py: Python, _key: &PyObject, value: &PyObject) -> PyResult<()> {
+            self.last_set.set(try!(value.extract(py)));
+            Ok(())
+        }
+    }
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let t = PyRustTypeBuilder::<Doubler>::new(py, "Doubler").mapping().finish().unwrap();
+    let inst = t.create_instance(py, Doubler { last_set: Cell::new(0) }, ());
+    let d = PyDict::new(py);
+    d.set_item(py, "o", &inst).unwrap();
+    py.run("assert o[21] == 42\nassert o[-3] == -6", None, Some(&d)).unwrap();
+    py.run("o[0] = 7", None, Some(&d)).unwrap();
+    assert_eq!(7, inst.get(py).last_set.get());
+    assert!(py.run("o['x']", None, Some(&d)).is_err());
+    assert!(py.run("del o[0]", None, Some(&d)).is_err());
+}
+
+#[test]
+fn iterator() {
+    struct Counter { next: Cell<i32>, end: i32 }
+    impl PyRustIterator for Counter {
+        fn next(&self, py: Python) -> PyResult<Option<PyObject>> {
+            let n = self.next.get();
+            if n >= self.end {
+                return Ok(None);
+            }
+            self.next.set(n + 1);
+            Ok(Some(n.to_py_object(py).into_object()))
+        }
+    }
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let t = PyRustTypeBuilder::<Counter>::new(py, "Counter").iterator().finish().unwrap();
+    let inst = t.create_instance(py, Counter { next: Cell::new(0), end: 3 }, ());
+    let d = PyDict::new(py);
+    d.set_item(py, "c", &inst).unwrap();
+    py.run("l = []\nfor i in c:\n    l.append(i)\nassert l == [0, 1, 2]", None, Some(&d)).unwrap();
+    // an exhausted iterator stays exhausted
+    py.run("assert list(c) == []", None, Some(&d)).unwrap();
+}
@@ -16,16 +16,17 @@
 // OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-use std::{ptr, marker};
+use std::{fmt, ptr, marker, panic};
+use std::fmt::Write;
 use std::ffi::{CStr, CString};
 use libc;
 use ffi;
 use python::{Python, ToPythonPointer, PythonObject, PyClone};
 use conversion::ToPyObject;
-use objects::{PyObject, PyType, PyString, PyModule, PyDict};
-use err::{self, PyResult};
+use objects::{exc, PyObject, PyType, PyString, PyModule, PyDict};
+use err::{self, PyErr, PyResult};
 use objectprotocol::ObjectProtocol;
-use super::{PythonBaseObject, PyRustObject, PyRustType};
+use super::{PythonBaseObject, PyRustObject, PyRustType, PyRustMapping, PyRustIterator};
 
 #[repr(C)]
 #[must_use]
@@ -90,6 +91,104 @@ unsafe extern "C" fn tp_dealloc_callback<T, B>(obj: *mut ffi::PyObject)
     });
 }
 
+unsafe extern "C" fn tp_str_callback<T, B>(obj: *mut ffi::PyObject) -> *mut ffi::PyObject
+        where T: 'static + Send + fmt::Display, B: PythonBaseObject {
+    abort_on_panic!({
+        let py = Python::assume_gil_acquired();
+        let slf = PyObject::from_borrowed_ptr(py, obj);
+        let slf = PyRustObject::<T, B>::unchecked_downcast_borrow_from(&slf);
+        format_to_py_string(py, |s| write!(s, "{}", slf.get(py)))
+    })
+}
+
+unsafe extern "C" fn tp_repr_callback<T, B>(obj: *mut ffi::PyObject) -> *mut ffi::PyObject
+        where T: 'static + Send + fmt::Debug, B: PythonBaseObject {
+    abort_on_panic!({
+        let py = Python::assume_gil_acquired();
+        let slf = PyObject::from_borrowed_ptr(py, obj);
+        let slf = PyRustObject::<T, B>::unchecked_downcast_borrow_from(&slf);
+        format_to_py_string(py, |s| write!(s, "{:?}", slf.get(py)))
+    })
+}
+
+unsafe extern "C" fn mp_subscript_callback<T, B>(obj: *mut ffi::PyObject, key: *mut ffi::PyObject) -> *mut ffi::PyObject
+        where T: 'static + Send + PyRustMapping, B: PythonBaseObject {
+    abort_on_panic!({
+        let py = Python::assume_gil_acquired();
+        let slf = PyObject::from_borrowed_ptr(py, obj);
+        let slf = PyRustObject::<T, B>::unchecked_downcast_borrow_from(&slf);
+        let key = PyObject::from_borrowed_ptr(py, key);
+        match slf.get(py).get_item(py, &key) {
+            Ok(val) => val.steal_ptr(),
+            Err(e) => {
+                e.restore(py);
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+unsafe extern "C" fn mp_ass_subscript_callback<T, B>(obj: *mut ffi::PyObject, key: *mut ffi::PyObject, value: *mut ffi::PyObject) -> libc::c_int
+        where T: 'static + Send + PyRustMapping, B: PythonBaseObject {
+    abort_on_panic!({
+        let py = Python::assume_gil_acquired();
+        let slf = PyObject::from_borrowed_ptr(py, obj);
+        let slf = PyRustObject::<T, B>::unchecked_downcast_borrow_from(&slf);
+        let key = PyObject::from_borrowed_ptr(py, key);
+        // a NULL value means the item is being deleted
+        let result = if value.is_null() {
+            slf.get(py).del_item(py, &key)
+        } else {
+            slf.get(py).set_item(py, &key, &PyObject::from_borrowed_ptr(py, value))
+        };
+        match result {
+            Ok(()) => 0,
+            Err(e) => {
+                e.restore(py);
+                -1
+            }
+        }
+    })
+}
+
+unsafe extern "C" fn tp_iter_callback(obj: *mut ffi::PyObject) -> *mut ffi::PyObject {
+    ffi::Py_INCREF(obj);
+    obj
+}
+
+unsafe extern "C" fn tp_iternext_callback<T, B>(obj: *mut ffi::PyObject) -> *mut ffi::PyObject
+        where T: 'static + Send + PyRustIterator, B: PythonBaseObject {
+    abort_on_panic!({
+        let py = Python::assume_gil_acquired();
+        let slf = PyObject::from_borrowed_ptr(py, obj);
+        let slf = PyRustObject::<T, B>::unchecked_downcast_borrow_from(&slf);
+        match slf.get(py).next(py) {
+            Ok(Some(val)) => val.steal_ptr(),
+            // returning NULL without an exception set signals StopIteration
+            Ok(None) => ptr::null_mut(),
+            Err(e) => {
+                e.restore(py);
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Runs the formatting function and converts the result to a Python string.
+/// If formatting fails or panics, sets a `RuntimeError` and returns NULL.
+fn format_to_py_string<F>(py: Python, f: F) -> *mut ffi::PyObject
+        where F: FnOnce(&mut String) -> fmt::Result {
+    let mut s = String::new();
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| f(&mut s)));
+    let msg = match result {
+        Ok(Ok(())) => return s.to_py_object(py).into_object().steal_ptr(),
+        Ok(Err(_)) => "an error occurred when formatting the object",
+        Err(_) => "a panic occurred when formatting the object"
+    };
+    PyErr::new::<exc::RuntimeError, _>(py, msg).restore(py);
+    ptr::null_mut()
+}
+
 impl <'p, T> PyRustTypeBuilder<'p, T> where T: 'static + Send {
     /// Create a new type builder.
     ///
@@ -208,6 +307,115 @@ impl <'p, T, B> PyRustTypeBuilder<'p, T, B> where T: 'static + Send, B: PythonBa
         PyRustTypeBuilder { doc_str: Some(CString::new(doc_str).unwrap()), .. self }
     }
 
+    /// Uses the `Display` implementation of the Rust type as `__str__` of the Python type.
+    ///
+    /// A formatting error or a panic within the formatter results in a `RuntimeError`.
+    #[cfg(feature="python27-sys")]
+    pub fn display(mut self) -> Self where T: fmt::Display {
+        self.can_change_base = false;
+        unsafe { (*self.ht).ht_type.tp_str = Some(tp_str_callback::<T, B>) };
+        self
+    }
+
+    /// Uses the `Display` implementation of the Rust type as `__str__` of the Python type.
+    ///
+    /// A formatting error or a panic within the formatter results in a `RuntimeError`.
+    #[cfg(feature="python3-sys")]
+    pub fn display(mut self) -> Self where T: fmt::Display {
+        self.can_change_base = false;
+        self.slots.push(ffi::PyType_Slot {
+            slot: ffi::Py_tp_str,
+            pfunc: tp_str_callback::<T, B> as ffi::reprfunc as *mut libc::c_void
+        });
+        self
+    }
+
+    /// Uses the `Debug` implementation of the Rust type as `__repr__` of the Python type.
+    ///
+    /// A formatting error or a panic within the formatter results in a `RuntimeError`.
+    #[cfg(feature="python27-sys")]
+    pub fn debug(mut self) -> Self where T: fmt::Debug {
+        self.can_change_base = false;
+        unsafe { (*self.ht).ht_type.tp_repr = Some(tp_repr_callback::<T, B>) };
+        self
+    }
+
+    /// Uses the `Debug` implementation of the Rust type as `__repr__` of the Python type.
+    ///
+    /// A formatting error or a panic within the formatter results in a `RuntimeError`.
+    #[cfg(feature="python3-sys")]
+    pub fn debug(mut self) -> Self where T: fmt::Debug {
+        self.can_change_base = false;
+        self.slots.push(ffi::PyType_Slot {
+            slot: ffi::Py_tp_repr,
+            pfunc: tp_repr_callback::<T, B> as ffi::reprfunc as *mut libc::c_void
+        });
+        self
+    }
+
+    /// Uses the `PyRustMapping` implementation of the Rust type as
+    /// `__getitem__`, `__setitem__` and `__delitem__` of the Python type.
+    ///
+    /// The methods are installed in the mapping slots, which Python consults
+    /// for all subscript operations, including integer indices and slices.
+    #[cfg(feature="python27-sys")]
+    pub fn mapping(mut self) -> Self where T: PyRustMapping {
+        self.can_change_base = false;
+        unsafe {
+            (*self.ht).as_mapping.mp_subscript = Some(mp_subscript_callback::<T, B>);
+            (*self.ht).as_mapping.mp_ass_subscript = Some(mp_ass_subscript_callback::<T, B>);
+            (*self.ht).ht_type.tp_as_mapping = &mut (*self.ht).as_mapping;
+        }
+        self
+    }
+
+    /// Uses the `PyRustMapping` implementation of the Rust type as
+    /// `__getitem__`, `__setitem__` and `__delitem__` of the Python type.
+    ///
+    /// The methods are installed in the mapping slots, which Python consults
+    /// for all subscript operations, including integer indices and slices.
+    #[cfg(feature="python3-sys")]
+    pub fn mapping(mut self) -> Self where T: PyRustMapping {
+        self.can_change_base = false;
+        self.slots.push(ffi::PyType_Slot {
+            slot: ffi::Py_mp_subscript,
+            pfunc: mp_subscript_callback::<T, B> as ffi::binaryfunc as *mut libc::c_void
+        });
+        self.slots.push(ffi::PyType_Slot {
+            slot: ffi::Py_mp_ass_subscript,
+            pfunc: mp_ass_subscript_callback::<T, B> as ffi::objobjargproc as *mut libc::c_void
+        });
+        self
+    }
+
+    /// Uses the `PyRustIterator` implementation of the Rust type as `__next__`
+    /// of the Python type; `__iter__` returns the object itself.
+    #[cfg(feature="python27-sys")]
+    pub fn iterator(mut self) -> Self where T: PyRustIterator {
+        self.can_change_base = false;
+        unsafe {
+            (*self.ht).ht_type.tp_iter = Some(tp_iter_callback);
+            (*self.ht).ht_type.tp_iternext = Some(tp_iternext_callback::<T, B>);
+        }
+        self
+    }
+
+    /// Uses the `PyRustIterator` implementation of the Rust type as `__next__`
+    /// of the Python type; `__iter__` returns the object itself.
+    #[cfg(feature="python3-sys")]
+    pub fn iterator(mut self) -> Self where T: PyRustIterator {
+        self.can_change_base = false;
+        self.slots.push(ffi::PyType_Slot {
+            slot: ffi::Py_tp_iter,
+            pfunc: tp_iter_callback as ffi::getiterfunc as *mut libc::c_void
+        });
+        self.slots.push(ffi::PyType_Slot {
+            slot: ffi::Py_tp_iternext,
+            pfunc: tp_iternext_callback::<T, B> as ffi::iternextfunc as *mut libc::c_void
+        });
+        self
+    }
+
     /// Adds a new member to the type.
     #[cfg(feature="python27-sys")]
     pub fn add<M>(mut self, name: &str, val: M) -> Self
@@ -2,7 +2,7 @@
 #![plugin(interpolate_idents)]
 #[macro_use] extern crate cpython;
 
-use cpython::{PyResult, Python, NoArgs, ToPyObject, ObjectProtocol, PyDict, PyTuple};
+use cpython::{PyResult, Python, NoArgs, ToPyObject, ObjectProtocol, PyDict, PyTuple, PyModule, PyClosure, PythonObject};
 use std::sync::atomic;
 use std::sync::atomic::Ordering::Relaxed;
 
@@ -76,3 +76,64 @@ fn flexible_sig() {
     assert_eq!(obj.call(py, (1,2,3), Some(&dict)).unwrap().extract::<i32>(py).unwrap(), 203);
 }
 
+#[test]
+fn module_add_functions() {
+    fn f(_py: Python) -> PyResult<i32> {
+        Ok(1)
+    }
+
+    fn g(_py: Python, i: i32) -> PyResult<i32> {
+        Ok(i * 2)
+    }
+
+    fn h(py: Python, args: &PyTuple, _kwargs: Option<&PyDict>) -> PyResult<usize> {
+        Ok(args.len(py))
+    }
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let m = PyModule::new(py, "test_module").unwrap();
+    py_module_add_functions!(py, &m, [ f(), g(i: i32), h ]).unwrap();
+
+    assert_eq!(m.call(py, "f", NoArgs, None).unwrap().extract::<i32>(py).unwrap(), 1);
+    assert_eq!(m.call(py, "g", (21,), None).unwrap().extract::<i32>(py).unwrap(), 42);
+    assert_eq!(m.call(py, "h", (1, 2, 3), None).unwrap().extract::<i32>(py).unwrap(), 3);
+
+    // adding a function with an existing name fails
+    assert!(py_module_add_functions!(py, &m, [ f() ]).is_err());
+}
+
+#[test]
+fn closure_counter() {
+    use std::sync::Arc;
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let dropped = Arc::new(atomic::AtomicBool::new(false));
+    struct SetOnDrop(Arc<atomic::AtomicBool>);
+    impl Drop for SetOnDrop {
+        fn drop(&mut self) { self.0.store(true, Relaxed) }
+    }
+    let guard = SetOnDrop(dropped.clone());
+    let mut count = 0;
+    let counter = PyClosure::new(py, move |py, args, _kwargs| {
+        let _ = &guard;
+        let step = if args.len(py) > 0 { try!(args.get_item(py, 0).extract::<i32>(py)) } else { 1 };
+        count += step;
+        Ok(count.to_py_object(py).into_object())
+    });
+    let obj = counter.into_object();
+    assert_eq!(1, obj.call(py, NoArgs, None).unwrap().extract::<i32>(py).unwrap());
+    assert_eq!(2, obj.call(py, NoArgs, None).unwrap().extract::<i32>(py).unwrap());
+    assert_eq!(12, obj.call(py, (10,), None).unwrap().extract::<i32>(py).unwrap());
+    assert!(obj.call(py, ("x",), None).is_err());
+
+    let d = PyDict::new(py);
+    d.set_item(py, "counter", &obj).unwrap();
+    assert_eq!(13, py.eval("counter()", None, Some(&d)).unwrap().extract::<i32>(py).unwrap());
+
+    assert!(!dropped.load(Relaxed));
+    drop(d);
+    drop(obj);
+    assert!(dropped.load(Relaxed));
+}